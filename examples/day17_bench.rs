@@ -0,0 +1,60 @@
+//! Times day 17's searches on generated boards and checks they agree
+//!
+//! ```
+//! $ cargo run --release --example day17_bench
+//! ```
+
+use std::time::{Duration, Instant};
+
+use advent_of_code_23::day17::{generate_board, heat_loss_with, CrucibleRules, Strategy};
+
+/// The hashing search needs a few GB on the largest boards, so it stops here
+const MAX_HASHED_SIZE: usize = 400;
+
+fn time(input: &str, rules: &CrucibleRules, strategy: Strategy) -> (usize, Duration) {
+    let start = Instant::now();
+    let heat_loss = heat_loss_with(input, rules, strategy).unwrap();
+    return (heat_loss, start.elapsed());
+}
+
+fn main() {
+    println!(
+        "{:>5}  {:>6}  {:>10}  {:>12}  {:>12}  {:>10}",
+        "size", "rules", "heat loss", "dijkstra", "dense", "dense gain"
+    );
+    for size in [100, 200, 400, 1000] {
+        let board = generate_board(size, size, size as u64);
+        let mut across = CrucibleRules::part1();
+        across.start = (size / 2, 0);
+        across.targets = vec![(size / 2, size - 1)];
+        let rule_sets = [
+            ("part 1", CrucibleRules::part1()),
+            ("part 2", CrucibleRules::part2()),
+            ("across", across),
+        ];
+        for (name, rules) in rule_sets {
+            let (dense_loss, dense) = time(&board, &rules, Strategy::DenseDijkstra);
+
+            let hashed = if size <= MAX_HASHED_SIZE {
+                let (loss, elapsed) = time(&board, &rules, Strategy::Dijkstra);
+                assert_eq!(loss, dense_loss);
+                Some(elapsed)
+            } else {
+                None
+            };
+
+            // How many times faster the dense search is than the hashing one
+            let speedup =
+                |hashed: Duration| format!("{:.1}x", hashed.as_secs_f64() / dense.as_secs_f64());
+            println!(
+                "{:>5}  {:>6}  {:>10}  {:>12}  {:>12?}  {:>10}",
+                size,
+                name,
+                dense_loss,
+                hashed.map_or("-".to_string(), |d| format!("{:?}", d)),
+                dense,
+                hashed.map_or("-".to_string(), speedup)
+            );
+        }
+    }
+}
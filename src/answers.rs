@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::common::InvalidAnswersError;
+use crate::runner::{Outcome, RunResult};
+
+/// Known-correct answers, read from a plain text file with one `day part answer` entry per line.
+///     Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    expected: BTreeMap<(u32, u32), String>,
+}
+
+impl Answers {
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        return self.expected.get(&(day, part)).map(|s| s.as_str());
+    }
+
+    /// Every (day, part) with a known answer, in order
+    pub fn entries(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        return self.expected.keys().copied();
+    }
+
+    /// Compare results against the known answers. Results without a known answer are skipped,
+    ///     but a known answer which `in_scope` says should have been run and has no result (e.g.
+    ///     because there is no solver for it) is a mismatch.
+    pub fn check<F>(&self, results: &[RunResult], mut in_scope: F) -> Vec<Mismatch>
+    where
+        F: FnMut(u32, u32) -> bool,
+    {
+        let mut mismatches = vec![];
+
+        for r in results {
+            let expected = match self.get(r.day, r.part) {
+                Some(e) => e.to_string(),
+                None => continue,
+            };
+            let got = match &r.outcome {
+                Outcome::Answer(a) => {
+                    if a.to_string() == expected {
+                        continue;
+                    }
+                    a.to_string()
+                }
+                Outcome::MissingInput(p) => format!("missing input ({})", p.display()),
+                Outcome::Invalid(e) => format!("parse error: {}", e),
+                Outcome::Failed(e) => format!("error: {}", e),
+            };
+            mismatches.push(Mismatch {
+                day: r.day,
+                part: r.part,
+                expected,
+                got,
+            });
+        }
+
+        for (day, part) in self.entries().filter(|(d, p)| in_scope(*d, *p)) {
+            if results.iter().any(|r| (r.day, r.part) == (day, part)) {
+                continue;
+            }
+            mismatches.push(Mismatch {
+                day,
+                part,
+                expected: self.get(day, part).unwrap().to_string(),
+                got: "no result, as there is no solver for it".to_string(),
+            });
+        }
+
+        return mismatches;
+    }
+}
+
+impl FromStr for Answers {
+    type Err = InvalidAnswersError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut expected = BTreeMap::new();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let err = || InvalidAnswersError {
+                line: idx + 1,
+                text: line.to_string(),
+            };
+
+            let mut fields = line.split_whitespace();
+            let day: u32 = fields.next().and_then(|d| d.parse().ok()).ok_or_else(err)?;
+            let part: u32 = fields.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+            let answer = fields.next().ok_or_else(err)?;
+            if fields.next().is_some() {
+                return Err(err());
+            }
+
+            expected.insert((day, part), answer.to_string());
+        }
+
+        return Ok(Answers { expected });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub day: u32,
+    pub part: u32,
+    pub expected: String,
+    pub got: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "Day {}, part {}:\n  - expected: {}\n  + got:      {}",
+            self.day, self.part, self.expected, self.got
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Answer;
+    use std::path::PathBuf;
+
+    fn result(day: u32, part: u32, outcome: Outcome) -> RunResult {
+        return RunResult {
+            day,
+            part,
+            outcome,
+            input: None,
+            timing: None,
+            bench: None,
+        };
+    }
+
+    #[test]
+    fn parse_answers() {
+        let answers: Answers = "# day part answer\n1 1 142\n\n1 2 281\n5 2 46\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(answers.get(1, 1), Some("142"));
+        assert_eq!(answers.get(5, 2), Some("46"));
+        assert_eq!(answers.get(5, 1), None);
+        assert_eq!(answers.entries().count(), 3);
+
+        let err = "1 1 142\n1 two 281".parse::<Answers>().unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!("1 1 142 extra".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn check_results() {
+        let answers: Answers = "1 1 142\n1 2 281\n2 1 8".parse().unwrap();
+        let results = vec![
+            result(1, 1, Outcome::Answer(Answer::U32(142))),
+            result(1, 2, Outcome::Answer(Answer::U32(280))),
+            result(2, 1, Outcome::MissingInput(PathBuf::from("day02.txt"))),
+            result(3, 1, Outcome::Answer(Answer::U32(4361))),
+        ];
+
+        let mismatches = answers.check(&results, |_, _| false);
+
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].expected, "281");
+        assert_eq!(mismatches[0].got, "280");
+        assert_eq!(mismatches[1].day, 2);
+        assert_eq!(mismatches[1].got, "missing input (day02.txt)");
+
+        let answers: Answers = "1 1 142\n19 1 42\n19 2 7".parse().unwrap();
+        let mismatches = answers.check(&results, |_, part| part == 1);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!((mismatches[0].day, mismatches[0].part), (19, 1));
+        assert_eq!(mismatches[0].expected, "42");
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::day17::CrucibleArgs;
+use crate::report::OutputFormat;
+use crate::runner::DayRange;
+
+#[derive(Parser)]
+#[command(author, version, about)]
+pub struct Cli {
+    /// manual input
+    pub input: Option<String>,
+
+    /// path to data, or - for stdin. May be given several times to run each file in turn.
+    /// Piped stdin is read when neither this nor an inline input is given
+    #[arg(short, long, value_name = "FILE")]
+    pub file: Vec<PathBuf>,
+
+    /// day number to run
+    #[arg(short, long, value_parser=clap::value_parser!(u32).range(1..26), required_unless_present_any = ["list", "all", "days", "check"])]
+    pub day: Option<u32>,
+
+    /// problem component (1 or 2); runs every part with --all, --days or --check when omitted
+    #[arg(short, long, value_parser=clap::value_parser!(u32).range(1..3), required_unless_present_any = ["list", "all", "days", "check"])]
+    pub part: Option<u32>,
+
+    /// list the implemented days and parts
+    #[arg(short, long)]
+    pub list: bool,
+
+    /// run every implemented day, reading inputs from --input-dir
+    #[arg(short, long, conflicts_with_all = ["day", "days"])]
+    pub all: bool,
+
+    /// run a range of days (e.g. 5, 3..10 or 3..=10), reading inputs from --input-dir
+    #[arg(long, value_name = "RANGE", conflicts_with = "day")]
+    pub days: Option<DayRange>,
+
+    /// directory holding inputs named dayNN.txt, used by --all and --days
+    #[arg(long, value_name = "DIR", default_value = "inputs")]
+    pub input_dir: PathBuf,
+
+    /// report the time spent splitting the input into lines and solving
+    #[arg(short, long)]
+    pub time: bool,
+
+    /// solve each part N times and report min/median/max solve times
+    #[arg(long, value_name = "N", value_parser=clap::value_parser!(u64).range(1..))]
+    pub bench: Option<u64>,
+    /// compare answers against a file of `day part answer` lines, exiting non-zero on mismatch.
+    /// Runs every day and part in the file unless --day, --all or --days are given
+    #[arg(short, long, value_name = "FILE")]
+    pub check: Option<PathBuf>,
+    /// how to print results
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// read the input a line at a time instead of all at once, for days which support it
+    #[arg(long, requires = "part", conflicts_with_all = ["all", "days", "bench"])]
+    pub stream: bool,
+
+    #[command(flatten)]
+    pub crucible: CrucibleArgs,
+}
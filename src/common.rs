@@ -1,41 +1,181 @@
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::error;
 use std::fmt;
 use std::fs::read_to_string;
 use std::hash::Hash;
-use std::path::PathBuf;
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
 
-use clap::Parser;
-
-#[derive(Parser)]
-#[command(author, version, about)]
-pub struct Cli {
-    /// manual input
-    pub input: Option<String>,
+pub fn fname_to_string(f: &str) -> String {
+    return read_to_string(f).unwrap();
+}
 
-    /// path to data
-    #[arg(short, long, value_name = "FILE")]
-    pub file: Option<PathBuf>,
+pub fn fname_to_lines(f: &str) -> Vec<String> {
+    return string_to_lines(&fname_to_string(&f));
+}
 
-    /// day number to run
-    #[arg(short, long, value_parser=clap::value_parser!(u32).range(1..26))]
-    pub day: u32,
+/// Read an input file, treating `-` as stdin, keeping hold of the path on failure
+pub fn read_input(path: &Path) -> Result<String, InputError> {
+    let result = if path == Path::new("-") {
+        let mut s = String::new();
+        io::stdin().read_to_string(&mut s).map(|_| s)
+    } else {
+        read_to_string(path)
+    };
 
-    /// problem component (1 or 2)
-    #[arg(short, long, value_parser=clap::value_parser!(u32).range(1..3))]
-    pub part: u32,
+    return result.map_err(|source| InputError {
+        path: path.to_path_buf(),
+        source,
+    });
 }
 
-pub fn fname_to_string(f: &str) -> String {
-    return read_to_string(f).unwrap();
+/// The lines of the input, borrowed from it. Drops `\n` or `\r\n` line endings, including a
+///     stray `\r` on the last line.
+pub fn input_lines(s: &str) -> impl Iterator<Item = &str> {
+    return s.lines().map(|l| l.strip_suffix('\r').unwrap_or(l));
 }
 
-pub fn fname_to_lines(f: &str) -> Vec<String> {
-    return string_to_lines(&fname_to_string(&f));
+/// Run `f` on the lines of `s`, for entry points taking the whole input in front of ones taking
+///     lines which have already been split
+pub fn with_lines<T>(s: &str, f: impl FnOnce(&[&str]) -> T) -> T {
+    let lines: Vec<&str> = input_lines(s).collect();
+    return f(&lines);
 }
 
+/// `input_lines`, copied into owned strings
 pub fn string_to_lines(s: &str) -> Vec<String> {
-    return s.lines().map(String::from).collect();
+    return input_lines(s).map(String::from).collect();
+}
+
+fn is_blank(l: &str) -> bool {
+    return l.trim().is_empty();
+}
+
+/// The blocks of text between blank lines, as slices of `s` without their final line ending, each
+///     with the index of its first line. Any number of blank (or whitespace only) lines separate
+///     blocks, and either line ending works.
+pub fn blocks(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut lines = s
+        .split_inclusive('\n')
+        .scan(0, |start, l| {
+            let line_start = *start;
+            *start += l.len();
+            Some((line_start, l))
+        })
+        .enumerate()
+        .peekable();
+    let line_end = |start: usize, l: &str| start + l.trim_end_matches(&['\r', '\n'][..]).len();
+
+    return std::iter::from_fn(move || {
+        let (idx, (start, first)) = lines.find(|(_, (_, l))| !is_blank(l))?;
+        let mut end = line_end(start, first);
+        while let Some((_, (line_start, l))) = lines.next_if(|(_, (_, l))| !is_blank(l)) {
+            end = line_end(line_start, l);
+        }
+        Some((idx, &s[start..end]))
+    });
+}
+
+/// The input back from the pieces a solver's `split` gave, for days which keep it whole. Input
+///     which was split into lines anyway is joined back up.
+pub fn unsplit<'a>(pieces: &[&'a str]) -> Cow<'a, str> {
+    return match pieces {
+        [input] => Cow::Borrowed(input),
+        lines => Cow::Owned(lines.join("\n")),
+    };
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    return bytes
+        .iter()
+        .fold(hash, |acc, b| (acc ^ *b as u64).wrapping_mul(0x100000001b3));
+}
+
+/// FNV-1a hash of the input, as hex. Stable across runs and platforms, unlike `DefaultHasher`
+pub fn input_hash(s: &str) -> String {
+    return format!("{:016x}", fnv1a(FNV_OFFSET, s.as_bytes()));
+}
+
+/// Wraps a reader, hashing everything read through it the same way as `input_hash`
+pub struct HashingReader<R> {
+    inner: R,
+    hash: u64,
+}
+
+impl<R: BufRead> HashingReader<R> {
+    pub fn new(inner: R) -> HashingReader<R> {
+        return HashingReader {
+            inner,
+            hash: FNV_OFFSET,
+        };
+    }
+
+    /// Hash of everything read so far, as hex
+    pub fn hash(&self) -> String {
+        return format!("{:016x}", self.hash);
+    }
+}
+
+impl<R: BufRead> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hash = fnv1a(self.hash, &buf[..n]);
+        return Ok(n);
+    }
+}
+
+impl<R: BufRead> BufRead for HashingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        return self.inner.fill_buf();
+    }
+    fn consume(&mut self, amt: usize) {
+        // The buffer is already filled, so this hands back the same bytes without reading
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.hash = fnv1a(self.hash, &buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+/// Fold over the records of `reader` which end at `delimiter` (or the end of the input), each
+///     with its index. Records are given without their delimiter or a `\r\n` line ending, and
+///     share one buffer, so nothing is held on to between them.
+pub fn try_fold_records<R: BufRead, A>(
+    mut reader: R,
+    delimiter: u8,
+    init: A,
+    mut f: impl FnMut(A, usize, &str) -> Result<A, ParseError>,
+) -> Result<A, Box<dyn error::Error>> {
+    let mut buf = vec![];
+    let mut acc = init;
+    let mut idx = 0;
+
+    while reader.read_until(delimiter, &mut buf)? > 0 {
+        let mut record: &[u8] = &buf;
+        for end in [delimiter, b'\n', b'\r'] {
+            record = record.strip_suffix(&[end]).unwrap_or(record);
+        }
+        let record = std::str::from_utf8(record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        acc = f(acc, idx, record)?;
+        idx += 1;
+        buf.clear();
+    }
+
+    return Ok(acc);
+}
+
+/// `try_fold_records` over the lines of `reader`
+pub fn try_fold_lines<R: BufRead, A>(
+    reader: R,
+    init: A,
+    f: impl FnMut(A, usize, &str) -> Result<A, ParseError>,
+) -> Result<A, Box<dyn error::Error>> {
+    return try_fold_records(reader, b'\n', init, f);
 }
 
 /*
@@ -80,6 +220,24 @@ impl fmt::Display for NoInputError {
 
 impl error::Error for NoInputError {}
 
+#[derive(Debug)]
+pub struct InputError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Unable to read {}: {}", self.path.display(), self.source);
+    }
+}
+
+impl error::Error for InputError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        return Some(&self.source);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NotImplementedError;
 
@@ -90,3 +248,340 @@ impl fmt::Display for NotImplementedError {
 }
 
 impl error::Error for NotImplementedError {}
+
+/// Malformed puzzle input, located by (1-based) line and column within the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,
+    pub column: usize,
+    /// The full line containing the problem
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Error on the `line_idx`th line (0-based), starting `col_idx` characters in (0-based)
+    pub fn new(
+        day: u32,
+        line_idx: usize,
+        col_idx: usize,
+        text: &str,
+        message: impl Into<String>,
+    ) -> ParseError {
+        return ParseError {
+            day,
+            line: line_idx + 1,
+            column: col_idx + 1,
+            text: text.to_string(),
+            message: message.into(),
+        };
+    }
+
+    /// Error pointing at `sub`, which must be a slice of `text`
+    pub fn at(
+        day: u32,
+        line_idx: usize,
+        text: &str,
+        sub: &str,
+        message: impl Into<String>,
+    ) -> ParseError {
+        return ParseError::new(day, line_idx, column_of(text, sub), text, message);
+    }
+
+    /// Error from a failed nom parser run over `text`, pointing at where the parser gave up
+    pub fn from_nom(
+        day: u32,
+        line_idx: usize,
+        text: &str,
+        err: nom::Err<nom::error::Error<&str>>,
+        message: impl Into<String>,
+    ) -> ParseError {
+        let col_idx = match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => column_of(text, e.input),
+            nom::Err::Incomplete(_) => text.len(),
+        };
+        return ParseError::new(day, line_idx, col_idx, text, message);
+    }
+
+    /// Multi-line diagnostic with the offending line and a caret under the problem, naming the
+    ///     file the input came from when known
+    pub fn render(&self, path: Option<&Path>) -> String {
+        let source = match path {
+            Some(p) if p == Path::new("-") => "stdin, ".to_string(),
+            Some(p) => format!("{}, ", p.display()),
+            None => String::new(),
+        };
+        let gutter = " ".repeat(self.line.to_string().len());
+        let caret_pad: String = self
+            .text
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        return format!(
+            "error: day {}: {}\n{}--> {}line {}, column {}\n{} |\n{} | {}\n{} | {}^",
+            self.day,
+            self.message,
+            gutter,
+            source,
+            self.line,
+            self.column,
+            gutter,
+            self.line,
+            self.text,
+            gutter,
+            caret_pad
+        );
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "day {}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        );
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Character offset of `sub` within `text`, where `sub` is a slice of `text`. Anything else
+///     (e.g. an owned copy) is reported as the start of the line.
+pub fn column_of(text: &str, sub: &str) -> usize {
+    let start = text.as_ptr() as usize;
+    let offset = (sub.as_ptr() as usize).wrapping_sub(start);
+
+    if offset > text.len() || !text.is_char_boundary(offset) {
+        return 0;
+    }
+    return text[..offset].chars().count();
+}
+
+#[derive(Debug, Clone)]
+pub struct InvalidDayRangeError(pub String);
+
+impl fmt::Display for InvalidDayRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "Invalid day range '{}', expected e.g. 5, 3..10 or 3..=10",
+            self.0
+        );
+    }
+}
+
+impl error::Error for InvalidDayRangeError {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidCrucibleError(pub String);
+
+impl fmt::Display for InvalidCrucibleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Invalid crucible rules: {}", self.0);
+    }
+}
+
+impl error::Error for InvalidCrucibleError {}
+
+/// No chain of almanac maps, followed either way, leads from one category to another
+#[derive(Debug, Clone)]
+pub struct NoRouteError {
+    pub from: String,
+    pub to: String,
+}
+
+impl fmt::Display for NoRouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "no maps lead from {} to {}", self.from, self.to);
+    }
+}
+
+impl error::Error for NoRouteError {}
+
+/// A crucible can't get from its start to any of its targets under its rules
+#[derive(Debug, Clone)]
+pub struct UnreachableError {
+    pub start: (usize, usize),
+    pub targets: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for UnreachableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let targets: Vec<String> = self
+            .targets
+            .iter()
+            .map(|(row, col)| format!("{},{}", row, col))
+            .collect();
+        return write!(
+            f,
+            "no route leads from {},{} to {}",
+            self.start.0,
+            self.start.1,
+            targets.join(" or ")
+        );
+    }
+}
+
+impl error::Error for UnreachableError {}
+
+/// `--stream` asked of a day which needs its whole input at once
+#[derive(Debug, Clone)]
+pub struct NotStreamableError(pub u32);
+
+impl fmt::Display for NotStreamableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "Day {} needs its whole input, so can't be streamed",
+            self.0
+        );
+    }
+}
+
+impl error::Error for NotStreamableError {}
+
+#[derive(Debug, Clone)]
+pub struct IncompleteRunError {
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for IncompleteRunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "{} of {} parts did not produce an answer",
+            self.failed, self.total
+        );
+    }
+}
+
+impl error::Error for IncompleteRunError {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidAnswersError {
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for InvalidAnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "Invalid answer on line {}: '{}', expected 'day part answer'",
+            self.line, self.text
+        );
+    }
+}
+
+impl error::Error for InvalidAnswersError {}
+
+#[derive(Debug, Clone)]
+pub struct CheckFailedError {
+    pub mismatches: usize,
+}
+
+impl fmt::Display for CheckFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} answers did not match", self.mismatches);
+    }
+}
+
+impl error::Error for CheckFailedError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_error_caret() {
+        let text = "Card 1: 41 4x | 83";
+        let bad = &text[11..13];
+        let err = ParseError::at(4, 9, text, bad, "invalid number");
+
+        assert_eq!(err.line, 10);
+        assert_eq!(err.column, 12);
+        assert_eq!(err.to_string(), "day 4, line 10, column 12: invalid number");
+        assert_eq!(
+            err.render(None),
+            "error: day 4: invalid number\n  --> line 10, column 12\n   |\n10 | Card 1: 41 4x | 83\n   |            ^"
+        );
+        assert!(err
+            .render(Some(Path::new("inputs/day04.txt")))
+            .contains("--> inputs/day04.txt, line 10, column 12"));
+    }
+
+    #[test]
+    fn blank_line_blocks() {
+        let unix = "\n#.#\n..#\n\n\n\n##\n\n";
+        let windows = "\r\n#.#\r\n..#\r\n\r\n \r\n\r\n##\r\n\r\n";
+        assert_eq!(
+            blocks(unix).collect::<Vec<_>>(),
+            vec![(1, "#.#\n..#"), (6, "##")]
+        );
+        assert_eq!(
+            blocks(windows).collect::<Vec<_>>(),
+            vec![(1, "#.#\r\n..#"), (6, "##")]
+        );
+        assert_eq!(blocks("no trailing newline").count(), 1);
+        assert_eq!(blocks("\n \r\n").count(), 0);
+
+        let lines = string_to_lines(windows);
+        assert_eq!(lines[1], "#.#");
+        assert_eq!(string_to_lines("a\r\nb\r"), vec!["a", "b"]);
+
+        assert_eq!(unsplit(&[windows]), windows);
+        assert_eq!(unsplit(&["a", "", "b"]), "a\n\nb");
+    }
+
+    #[test]
+    fn streamed_records() {
+        let input = "1,2\r\n3,4\n\n5";
+        let mut reader = HashingReader::new(input.as_bytes());
+        let lines = try_fold_lines(&mut reader, vec![], |mut acc, idx, l| {
+            acc.push((idx, l.to_string()));
+            Ok(acc)
+        })
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (0, "1,2".to_string()),
+                (1, "3,4".to_string()),
+                (2, String::new()),
+                (3, "5".to_string())
+            ]
+        );
+        assert_eq!(reader.hash(), input_hash(input));
+
+        let sum = try_fold_records("1,2,3\n".as_bytes(), b',', 0, |acc, idx, t| {
+            t.parse::<u32>()
+                .map(|v| acc + v)
+                .map_err(|_| ParseError::new(0, idx, 0, t, "invalid number"))
+        });
+        assert_eq!(sum.unwrap(), 6);
+
+        let err = try_fold_lines("1\nx".as_bytes(), 0, |acc, idx, l| {
+            l.parse::<u32>()
+                .map(|v| acc + v)
+                .map_err(|_| ParseError::new(0, idx, 0, l, "invalid number"))
+        })
+        .unwrap_err();
+        assert_eq!(err.downcast::<ParseError>().unwrap().line, 2);
+        assert!(try_fold_lines(&[0xff_u8][..], 0, |acc, _, _| Ok(acc)).is_err());
+    }
+
+    #[test]
+    fn read_input_keeps_path() {
+        let path = Path::new("definitely/not/a/real/input.txt");
+        let err = read_input(path).unwrap_err();
+
+        assert_eq!(err.path, path);
+        assert_eq!(err.source.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("definitely/not/a/real/input.txt"));
+    }
+}
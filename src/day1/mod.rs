@@ -1,43 +1,91 @@
+use std::error::Error;
+use std::io::BufRead;
+
+use crate::common::{try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::solver::{Solver, SolverResult};
 use regex::Regex;
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let result = lines
+const DAY: u32 = 1;
+
+pub fn part1(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    return lines
         .iter()
-        .map(|l| {
-            let digits: Vec<u32> = l
-                .chars()
-                .filter(|c| c.is_numeric())
-                .into_iter()
-                .map(|c| c.to_digit(10).unwrap())
-                .collect();
-            let d1 = digits.first().unwrap();
-            let d2 = digits.last().unwrap();
-            10 * d1 + d2
-        })
-        .sum::<u32>();
-
-    return result;
+        .enumerate()
+        .map(|(idx, l)| digit_value(idx, l))
+        .sum();
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let r_forward = Regex::new(r"(one|two|three|four|five|six|seven|eight|nine|[0-9]).*").unwrap();
-    let r_reverse = Regex::new(r"(eno|owt|eerht|ruof|evif|xis|neves|thgie|enin|[0-9]).*").unwrap();
+pub fn part2(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part2_lines);
+}
 
-    let result = lines
+pub fn part2_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let spelled = SpelledDigits::new();
+    return lines
         .iter()
-        .map(|l| {
-            let c1 = r_forward.captures(&l).unwrap();
-            let temp = reverse_string(l);
-            let c2 = r_reverse.captures(&temp).unwrap();
+        .enumerate()
+        .map(|(idx, l)| spelled.value(idx, l))
+        .sum();
+}
 
-            let d1 = forward_to_digit(&c1[1]);
-            let d2 = reverse_to_digit(&c2[1]);
+/// `part1`, reading a line at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| Ok(acc + digit_value(idx, l)?));
+}
 
-            return 10 * d1 + d2;
-        })
-        .sum();
+/// `part2`, reading a line at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    let spelled = SpelledDigits::new();
+    return try_fold_lines(reader, 0, |acc, idx, l| Ok(acc + spelled.value(idx, l)?));
+}
+
+fn digit_value(idx: usize, l: &str) -> Result<u32, ParseError> {
+    let digits: Vec<u32> = l.chars().filter_map(|c| c.to_digit(10)).collect();
+    let (d1, d2) = match (digits.first(), digits.last()) {
+        (Some(d1), Some(d2)) => (d1, d2),
+        _ => return Err(ParseError::new(DAY, idx, 0, l, "no digits in line")),
+    };
+    return Ok(10 * d1 + d2);
+}
+
+/// Finds the first and last digits of a line, counting spelled out ones
+struct SpelledDigits {
+    forward: Regex,
+    reverse: Regex,
+}
+
+impl SpelledDigits {
+    fn new() -> SpelledDigits {
+        return SpelledDigits {
+            forward: Regex::new(r"(one|two|three|four|five|six|seven|eight|nine|[0-9]).*").unwrap(),
+            reverse: Regex::new(r"(eno|owt|eerht|ruof|evif|xis|neves|thgie|enin|[0-9]).*").unwrap(),
+        };
+    }
+
+    fn value(&self, idx: usize, l: &str) -> Result<u32, ParseError> {
+        let temp = reverse_string(l);
+        let (c1, c2) = match (self.forward.captures(l), self.reverse.captures(&temp)) {
+            (Some(c1), Some(c2)) => (c1, c2),
+            _ => {
+                return Err(ParseError::new(
+                    DAY,
+                    idx,
+                    0,
+                    l,
+                    "no digits or spelled out digits in line",
+                ))
+            }
+        };
 
-    return result;
+        let d1 = forward_to_digit(&c1[1]);
+        let d2 = reverse_to_digit(&c2[1]);
+
+        return Ok(10 * d1 + d2);
+    }
 }
 
 fn forward_to_digit(s: &str) -> u32 {
@@ -70,16 +118,43 @@ fn reverse_string(s: &str) -> String {
     return s.to_owned().chars().rev().collect();
 }
 
+pub struct Day1;
+
+impl Solver for Day1 {
+    fn day(&self) -> u32 {
+        1
+    }
+    fn name(&self) -> &'static str {
+        "Trebuchet?!"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part_1() -> () {
         let test_input: String = String::from("1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 142);
     }
@@ -87,9 +162,28 @@ mod tests {
     #[test]
     fn part_2() -> () {
         let test_input: String = String::from("two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen");
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 281);
     }
+
+    #[test]
+    fn no_digits() {
+        let test_lines = "1abc2\npqrstuvwx";
+        let err = part1(test_lines).unwrap_err();
+
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn streaming() {
+        let test_input = "1abc2\r\npqr3stu8vwx\r\na1b2c3d4e5f\r\ntreb7uchet\r\n";
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 142);
+
+        let test_input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen";
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 281);
+
+        assert!(part1_stream("1abc2\npqrstuvwx".as_bytes()).is_err());
+    }
 }
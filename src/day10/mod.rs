@@ -1,9 +1,14 @@
 /*
  I'm really pretty unhappy with this solution. I should've come up with better methods for
- Connection and Board to make this problem easier to grapple with, e.g. the `connects_X`
- methods I implemented at the end to get the starting pipe shape figured out.
+ Connection and Board to make this problem easier to grapple with, e.g. the `connects`
+ method I implemented at the end to get the starting pipe shape figured out.
 */
 
+use crate::common::{with_lines, NotImplementedError, ParseError};
+use crate::matrix::{AoCMatrix, Direction};
+use crate::parsers::{parse_char_grid, FromChar};
+use crate::search::{bfs, Search};
+use crate::solver::{Solver, SolverResult};
 use std::iter::zip;
 
 type LOCATION = (usize, usize);
@@ -20,9 +25,9 @@ enum Connection {
     Start,
 }
 
-impl Connection {
-    fn new(c: char) -> Connection {
-        match c {
+impl FromChar for Connection {
+    fn from_char(c: char) -> Option<Connection> {
+        let connection = match c {
             '|' => Connection::NS,
             '-' => Connection::EW,
             'L' => Connection::NE,
@@ -31,209 +36,112 @@ impl Connection {
             'F' => Connection::SE,
             '.' => Connection::Empty,
             'S' => Connection::Start,
-            s => panic!("Unrecognized character in input: {}", s),
-        }
-    }
-    fn connects_south(&self) -> bool {
-        return [Connection::NS, Connection::SE, Connection::SW].contains(self);
-    }
-    fn connects_north(&self) -> bool {
-        return [Connection::NS, Connection::NE, Connection::NW].contains(self);
+            _ => return None,
+        };
+        return Some(connection);
     }
-    fn connects_east(&self) -> bool {
-        return [Connection::EW, Connection::SE, Connection::NE].contains(self);
+}
+
+impl Connection {
+    /// The directions the pipe leads off in, none for the ground and the unknown start
+    fn directions(&self) -> Vec<Direction> {
+        return match self {
+            Connection::NS => vec![Direction::North, Direction::South],
+            Connection::EW => vec![Direction::East, Direction::West],
+            Connection::NE => vec![Direction::North, Direction::East],
+            Connection::NW => vec![Direction::North, Direction::West],
+            Connection::SE => vec![Direction::South, Direction::East],
+            Connection::SW => vec![Direction::South, Direction::West],
+            Connection::Empty | Connection::Start => vec![],
+        };
     }
-    fn connects_west(&self) -> bool {
-        return [Connection::EW, Connection::SW, Connection::NW].contains(self);
+    fn connects(&self, dir: Direction) -> bool {
+        return self.directions().contains(&dir);
     }
 }
 
 struct Board {
-    characters: Vec<Vec<Connection>>,
-    dists: Vec<Vec<i64>>,
+    characters: AoCMatrix<Connection>,
+    start: LOCATION,
 }
 
-#[derive(Copy, Clone, Debug)]
-struct ExploreStep {
-    // Distance leading to this point
-    dist_in: i64,
+const DAY: u32 = 10;
 
-    // Location of the point to be explored
-    location: LOCATION,
+pub fn part1(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part1_lines);
 }
 
-impl ExploreStep {
-    fn new(dist_in: i64, location: LOCATION) -> ExploreStep {
-        ExploreStep { dist_in, location }
-    }
-}
-
-pub fn part1(lines: Vec<String>) -> i64 {
-    let board = make_board(lines);
-    let board = populate_dists(board);
+pub fn part1_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let board = make_board(lines)?;
+    let pipes = explore_pipes(&board);
 
-    let mut dists = board.dists;
-
-    let result = dists
-        .iter_mut()
-        .reduce(|a, b| {
-            a.append(b);
-            a
-        })
-        .unwrap()
-        .iter()
-        .filter(|v| v.is_positive())
-        .max()
-        .unwrap();
+    // The start always connects to something, so there's at least one other distance
+    let result = pipes.reached_states().map(|(_, d)| d).max().unwrap();
 
-    return result.to_owned();
+    return Ok(result as i64);
 }
 
-fn populate_dists(mut board: Board) -> Board {
-    let start = find_start(&board);
-    let mut explore_queue = generate_start_queue(&board, start);
-
-    loop {
-        let step = explore_queue[0];
-
-        // This updates in-place
-        update_board_queue(&mut board, step, &mut explore_queue);
-
-        if explore_queue.len() == 1 {
-            break;
-        } else {
-            explore_queue = explore_queue[1..].to_vec();
-        }
-    }
-
-    return board;
+/// Distances along the pipes from the start to every square they lead to
+fn explore_pipes(board: &Board) -> Search<LOCATION, usize> {
+    return bfs([board.start], |pos| next_steps(board, *pos));
 }
 
-fn update_board_queue(board: &mut Board, step: ExploreStep, queue: &mut Vec<ExploreStep>) {
-    let (row, col) = step.location;
-    let board_dist = board.dists[row][col];
-    let cur_connection = board_index(board, row, col).unwrap().to_owned();
-
-    if board_dist.is_negative() || (!board_dist.is_negative() && step.dist_in < board_dist) {
-        board.dists[row][col] = step.dist_in;
-
-        if let Some((a, b)) = next_step(step, cur_connection) {
-            queue.push(a);
-            queue.push(b);
-        }
+fn next_steps(board: &Board, pos: LOCATION) -> Vec<LOCATION> {
+    if pos == board.start {
+        return start_steps(board);
     }
-    return ();
+    return board.characters[pos]
+        .directions()
+        .into_iter()
+        // Pipes leading off the edge of the board go nowhere
+        .filter_map(|d| board.characters.step(pos, d, 1))
+        .collect();
 }
 
-fn next_step(
-    cur_step: ExploreStep,
-    cur_connection: Connection,
-) -> Option<(ExploreStep, ExploreStep)> {
-    let ndist = cur_step.dist_in + 1;
-    let (crow, ccol) = cur_step.location;
-    let n = (crow - 1, ccol);
-    let s = (crow + 1, ccol);
-    let e = (crow, ccol + 1);
-    let w = (crow, ccol - 1);
-
-    return match cur_connection {
-        Connection::EW => Some((ExploreStep::new(ndist, e), ExploreStep::new(ndist, w))),
-        Connection::NS => Some((ExploreStep::new(ndist, n), ExploreStep::new(ndist, s))),
-        Connection::NE => Some((ExploreStep::new(ndist, n), ExploreStep::new(ndist, e))),
-        Connection::NW => Some((ExploreStep::new(ndist, n), ExploreStep::new(ndist, w))),
-        Connection::SE => Some((ExploreStep::new(ndist, s), ExploreStep::new(ndist, e))),
-        Connection::SW => Some((ExploreStep::new(ndist, s), ExploreStep::new(ndist, w))),
-        _ => None,
-    };
+/// The start's shape is unknown, so follow every neighbouring pipe which leads back into it
+fn start_steps(board: &Board) -> Vec<LOCATION> {
+    return start_directions(board)
+        .into_iter()
+        .filter_map(|d| board.characters.step(board.start, d, 1))
+        .collect();
 }
 
-fn generate_start_queue(board: &Board, start: LOCATION) -> Vec<ExploreStep> {
-    let w = (start.0, start.1 - 1);
-    let e = (start.0, start.1 + 1);
-    let s = (start.0 + 1, start.1);
-    let n = (start.0 - 1, start.1);
-
-    let mut queue: Vec<ExploreStep> = vec![];
-    match board_index(&board, n.0, n.1) {
-        None => (),
-        Some(c) => {
-            if [Connection::NS, Connection::SW, Connection::SE].contains(&c) {
-                queue.push(ExploreStep::new(1, n))
-            }
-        }
-    }
-    match board_index(&board, s.0, s.1) {
-        None => (),
-        Some(c) => {
-            if [Connection::NS, Connection::NW, Connection::NE].contains(&c) {
-                queue.push(ExploreStep::new(1, s))
-            }
-        }
-    }
-    match board_index(&board, e.0, e.1) {
-        None => (),
-        Some(c) => {
-            if [Connection::EW, Connection::SW, Connection::NW].contains(&c) {
-                queue.push(ExploreStep::new(1, e))
-            }
-        }
-    }
-    match board_index(&board, w.0, w.1) {
-        None => (),
-        Some(c) => {
-            if [Connection::EW, Connection::NE, Connection::SE].contains(&c) {
-                queue.push(ExploreStep::new(1, w))
-            }
-        }
-    }
-
-    return queue;
+fn start_directions(board: &Board) -> Vec<Direction> {
+    let chars = &board.characters;
+    return Direction::ALL
+        .into_iter()
+        .filter(|d| {
+            chars
+                .step(board.start, *d, 1)
+                .map_or(false, |n| chars[n].connects(d.reverse()))
+        })
+        .collect();
 }
 
-fn board_index(b: &Board, row: usize, col: usize) -> Option<&Connection> {
-    let rows = b.characters.len();
-    let cols = b.characters[0].len();
+fn make_board(lines: &[&str]) -> Result<Board, ParseError> {
+    let characters = parse_char_grid(DAY, 0, lines)?;
 
-    if row < rows && col < cols {
-        return Some(&b.characters[row][col]);
-    } else {
-        return None;
+    let start = match find_start(&characters) {
+        Some(start) => start,
+        // Not any one square's fault, so it points at the top of the board, which parse_char_grid
+        //     has checked is there
+        None => return Err(ParseError::new(DAY, 0, 0, lines[0], "missing start S")),
     };
-}
-
-fn make_board(lines: Vec<String>) -> Board {
-    let characters = lines.iter().map(|l| parse_line(l)).collect();
-    let dists = dist_mat(lines);
 
-    return Board { characters, dists };
-}
-
-fn parse_line(l: &str) -> Vec<Connection> {
-    return l.chars().map(|c| Connection::new(c)).collect();
-}
-
-fn find_start(b: &Board) -> LOCATION {
-    for (row_idx, row) in b.characters.iter().enumerate() {
-        match row.iter().position(|p| p.eq(&Connection::Start)) {
-            Some(col_idx) => return (row_idx, col_idx),
-            None => (),
-        };
+    let board = Board { characters, start };
+    if start_steps(&board).is_empty() {
+        let msg = "start doesn't connect to any pipe";
+        return Err(ParseError::new(DAY, start.0, start.1, lines[start.0], msg));
     }
-    panic!("Couldn't find start on board");
+
+    return Ok(board);
 }
 
-fn dist_mat(l: Vec<String>) -> Vec<Vec<i64>> {
-    return l
-        .iter()
-        .map(|l| {
-            let length: usize = l.len();
-            let mut dist_row: Vec<i64> = vec![];
-            for _ in 0..length {
-                dist_row.push(-1);
-            }
-            dist_row
-        })
-        .collect();
+fn find_start(characters: &AoCMatrix<Connection>) -> Option<LOCATION> {
+    return characters
+        .indexed_iter()
+        .find(|(_, c)| **c == Connection::Start)
+        .map(|(pos, _)| pos);
 }
 
 /*
@@ -242,53 +150,67 @@ fn dist_mat(l: Vec<String>) -> Vec<Vec<i64>> {
    *************************************************************************************
 */
 
-pub fn part2(lines: Vec<String>) -> i64 {
-    let board = make_board(lines);
-    let mut board = populate_dists(board);
+pub fn part2(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part2_lines);
+}
 
-    let start = find_start(&board);
-    // let explore_queue = generate_start_queue(&board, start);
-    // let (a, b) = (explore_queue[0], explore_queue[1]);
+pub fn part2_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let mut board = make_board(lines)?;
+    let pipes = explore_pipes(&board);
 
-    let replace_connection = find_replace_connection(&board, start);
+    let start = board.start;
 
-    board.characters[start.0][start.1] = replace_connection;
+    let replace_connection = find_replace_connection(&board).ok_or_else(|| {
+        ParseError::new(
+            DAY,
+            start.0,
+            start.1,
+            lines[start.0],
+            "unknown starting shape",
+        )
+    })?;
 
-    let dists = board.dists;
+    board.characters[start] = replace_connection;
 
     // 0s are empty space, 1s are actual space
-    let in_out_board: Vec<Vec<i64>> = dists
-        .into_iter()
-        .map(|row| row.iter().map(|v| !v.is_negative() as i64).collect())
+    let in_out_board: Vec<Vec<i64>> = (0..board.characters.n_rows())
+        .map(|r| {
+            (0..board.characters.n_cols())
+                .map(|c| pipes.reached(&(r, c)) as i64)
+                .collect()
+        })
         .collect();
 
-    let in_out: i64 = zip(in_out_board, board.characters)
+    let in_out: i64 = zip(in_out_board, board.characters.rows_by_value())
         .map(|(bools, chars)| count_interior(&bools, &chars))
         .sum();
 
-    return in_out;
+    return Ok(in_out);
 }
 
-fn find_replace_connection(board: &Board, start: LOCATION) -> Connection {
-    let north = board.characters[start.0 - 1][start.1].connects_south();
-    let south = board.characters[start.0 + 1][start.1].connects_north();
-    let west = board.characters[start.0][start.1 - 1].connects_east();
-    let east = board.characters[start.0][start.1 + 1].connects_west();
+fn find_replace_connection(board: &Board) -> Option<Connection> {
+    let dirs = start_directions(board);
+    let connects = |d: Direction| dirs.contains(&d);
+
+    let north = connects(Direction::North);
+    let south = connects(Direction::South);
+    let west = connects(Direction::West);
+    let east = connects(Direction::East);
 
     if north && south {
-        return Connection::NS;
+        return Some(Connection::NS);
     } else if north && east {
-        return Connection::NE;
+        return Some(Connection::NE);
     } else if north && west {
-        return Connection::NW;
+        return Some(Connection::NW);
     } else if east && west {
-        return Connection::EW;
+        return Some(Connection::EW);
     } else if east && south {
-        return Connection::SE;
+        return Some(Connection::SE);
     } else if west && south {
-        return Connection::SW;
+        return Some(Connection::SW);
     } else {
-        panic!("Unknown starting shape");
+        return None;
     }
 }
 
@@ -331,27 +253,58 @@ fn count_interior(bool_entries: &Vec<i64>, pipe_entries: &Vec<Connection>) -> i6
     return acc;
 }
 
+pub struct Day10;
+
+impl Solver for Day10 {
+    fn day(&self) -> u32 {
+        10
+    }
+    fn name(&self) -> &'static str {
+        "Pipe Maze"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = ".....\n.S-7.\n.|.|.\n.L-J.\n.....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 4);
     }
     #[test]
     fn part2_test() {
         let string_input = ".....\n.S-7.\n.|.|.\n.L-J.\n.....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 1);
     }
+
+    #[test]
+    fn bad_board() {
+        let err = part1(".....\n.S-7.\n.|x|.").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+
+        let err = part1("...\n.-.\n...").unwrap_err();
+        assert_eq!(err.message, "missing start S");
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 1, "..."));
+
+        // A start in the corner connected to nothing shouldn't underflow
+        let err = part2("S.\n..").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
 }
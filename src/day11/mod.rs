@@ -1,173 +1,131 @@
+use crate::common::{with_lines, NotImplementedError, ParseError};
+use crate::parsers::parse_grid;
+use crate::solver::{Solver, SolverResult};
+use crate::sparse_grid::{Coord, SparseGrid};
 use std::collections::HashSet;
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
-struct Universe {
-    rows: u64,
-    cols: u64,
-    galaxies: Vec<Galaxy>,
-}
-
-impl Universe {
-    fn from_array_arrays(mut v: Vec<Vec<Galaxy>>, rows: u64, cols: u64) -> Universe {
-        let mut galaxies: Vec<Galaxy> = vec![];
-        for gs in v.iter_mut() {
-            galaxies.append(gs);
-        }
-        return Universe {
-            rows,
-            cols,
-            galaxies,
-        };
-    }
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
-struct Galaxy {
-    row: u64,
-    col: u64,
-}
+/// Galaxies as '#', at their (row, col) in the image
+type UNIVERSE = SparseGrid<char>;
 
-impl Galaxy {
-    fn mdist(&self, other: &Self) -> u64 {
-        let retval = self.row.abs_diff(other.row) + self.col.abs_diff(other.col);
+const DAY: u32 = 11;
 
-        return retval;
-    }
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part1_lines);
 }
 
-pub fn part1(lines: Vec<String>) -> u64 {
+pub fn part1_lines(lines: &[&str]) -> Result<u64, ParseError> {
     return logic(lines, 1);
 }
-pub fn part2(lines: Vec<String>) -> u64 {
-    return logic(lines, 1000000 - 1);
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part2_lines);
 }
 
-pub fn logic(lines: Vec<String>, factor: u64) -> u64 {
-    let rows = lines.len() as u64;
-    let cols = lines[0].len() as u64;
+pub fn part2_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    return logic(lines, 1000000 - 1);
+}
 
-    let galaxies: Vec<Vec<Galaxy>> = lines
+pub fn logic(lines: &[&str], factor: u64) -> Result<u64, ParseError> {
+    let image = parse_grid(DAY, 0, lines, |c| matches!(c, '.' | '#').then_some(c))?;
+    let universe: UNIVERSE = SparseGrid::from(&image)
         .iter()
-        .enumerate()
-        .map(|(row, l)| parse_line(l, row.try_into().unwrap()))
+        .filter(|(_, c)| **c == '#')
+        .map(|(pos, c)| (pos, *c))
         .collect();
 
-    let universe = Universe::from_array_arrays(galaxies, rows, cols);
-    let missing_rows = find_missing_rows(&universe);
-    let missing_cols = find_missing_cols(&universe);
-
-    let universe = expand_rows(universe, missing_rows, factor);
-    let universe = expand_cols(universe, missing_cols, factor);
-
-    let galaxies = universe.galaxies;
+    let galaxies: Vec<Coord> = expand(&universe, factor as i64).positions().collect();
     let mut acc = 0;
-    for i in 0..galaxies.len() {
-        let g1 = galaxies[i];
-        for j in (i + 1)..galaxies.len() {
-            let g2 = galaxies[j];
-            acc += g1.mdist(&g2);
+    for (i, g1) in galaxies.iter().enumerate() {
+        for g2 in galaxies[(i + 1)..].iter() {
+            acc += g1.0.abs_diff(g2.0) + g1.1.abs_diff(g2.1);
         }
     }
 
-    return acc;
+    return Ok(acc);
 }
 
-fn expand_rows(mut universe: Universe, miss_rows: HashSet<u64>, factor: u64) -> Universe {
-    // Sort in descending order of row
-    let mut galaxies = universe.galaxies;
-    galaxies.sort_by(|a, b| a.row.cmp(&b.row));
-
-    let mut mrows: Vec<u64> = miss_rows.into_iter().collect();
-    mrows.sort();
-
-    mrows.iter().rev().for_each(|mrow| {
-        galaxies.iter_mut().rev().for_each(|g| {
-            if mrow < &g.row {
-                g.row += factor;
-            }
-        })
-    });
-
-    universe.galaxies = galaxies;
-    return universe;
+/// Add `factor` more rows for every row without a galaxy, and the same for columns. Rows and
+///     columns outside the galaxies' bounds don't come between any of them, so are left alone.
+fn expand(universe: &UNIVERSE, factor: i64) -> UNIVERSE {
+    let (min, max) = match universe.bounds() {
+        Some(b) => b,
+        None => return SparseGrid::new(),
+    };
+    let rows: HashSet<i64> = universe.positions().map(|p| p.0).collect();
+    let cols: HashSet<i64> = universe.positions().map(|p| p.1).collect();
+    let empty_rows: Vec<i64> = (min.0..=max.0).filter(|r| !rows.contains(r)).collect();
+    let empty_cols: Vec<i64> = (min.1..=max.1).filter(|c| !cols.contains(c)).collect();
+
+    // Pushed along by every empty line before it
+    let shift = |empty: &[i64], v: i64| v + factor * empty.partition_point(|e| *e < v) as i64;
+
+    return universe
+        .iter()
+        .map(|(p, c)| ((shift(&empty_rows, p.0), shift(&empty_cols, p.1)), *c))
+        .collect();
 }
-fn expand_cols(mut universe: Universe, miss_cols: HashSet<u64>, factor: u64) -> Universe {
-    // Sort in descending order of row
-    let mut galaxies = universe.galaxies;
-    galaxies.sort_by(|a, b| a.col.cmp(&b.col));
-
-    let mut mcols: Vec<u64> = miss_cols.into_iter().collect();
-    mcols.sort();
-
-    mcols.iter().rev().for_each(|mcol| {
-        galaxies.iter_mut().rev().for_each(|g| {
-            if mcol < &g.col {
-                g.col += factor;
-            }
-        })
-    });
 
-    universe.galaxies = galaxies;
-    return universe;
-}
+pub struct Day11;
 
-fn find_missing_rows(u: &Universe) -> HashSet<u64> {
-    let mut h = HashSet::from_iter(0..u.rows);
-    for g in &u.galaxies {
-        h.remove(&g.row);
+impl Solver for Day11 {
+    fn day(&self) -> u32 {
+        11
     }
-
-    return h;
-}
-fn find_missing_cols(u: &Universe) -> HashSet<u64> {
-    let mut h = HashSet::from_iter(0..u.cols);
-    for g in &u.galaxies {
-        h.remove(&g.col);
+    fn name(&self) -> &'static str {
+        "Cosmic Expansion"
     }
-
-    return h;
-}
-
-fn parse_line(l: &str, row: u64) -> Vec<Galaxy> {
-    let substrs: Vec<&str> = l.split('#').collect();
-    let mut acc = 0;
-    let mut galaxies: Vec<Galaxy> = vec![];
-
-    if let Some((_, els)) = substrs.split_last() {
-        els.iter().for_each(|s| {
-            let l = s.len() as u64;
-            acc += l;
-            galaxies.push(Galaxy { row, col: acc });
-            acc += 1;
-        })
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
     }
-
-    return galaxies;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "...#......\n.......#..\n#.........\n..........\n......#...\n.#........\n.........#\n..........\n.......#..\n#...#.....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 374);
     }
     #[test]
     fn part2_test() {
         let string_input = "...#......\n.......#..\n#.........\n..........\n......#...\n.#........\n.........#\n..........\n.......#..\n#...#.....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = logic(line_input.clone(), 9);
+        let result = with_lines(line_input, |lines| logic(lines, 9)).unwrap();
         assert_eq!(result, 1030);
 
-        let result = logic(line_input, 99);
+        let result = with_lines(line_input, |lines| logic(lines, 99)).unwrap();
         assert_eq!(result, 8410);
     }
+
+    #[test]
+    fn expansion() {
+        let string_input = "...#......\n.......#..\n#.........\n..........\n......#...\n.#........\n.........#\n..........\n.......#..\n#...#.....";
+        let universe: UNIVERSE = with_lines(string_input, |lines| {
+            parse_grid(DAY, 0, lines, |c| (c == '#').then_some(c).or(Some('.')))
+        })
+        .map(|image| {
+            SparseGrid::from(&image)
+                .iter()
+                .filter(|(_, c)| **c == '#')
+                .map(|(pos, c)| (pos, *c))
+                .collect()
+        })
+        .unwrap();
+
+        assert_eq!(
+            expand(&universe, 1).to_string(),
+            "....#........\n.........#...\n#............\n.............\n.............\n........#....\n.#...........\n............#\n.............\n.............\n.........#...\n#....#......."
+        );
+        assert_eq!(expand(&SparseGrid::new(), 1), SparseGrid::new());
+    }
 }
@@ -1,40 +1,73 @@
 #![allow(unused)]
 
-use crate::parsers::{comma_separated, space_separated};
+use crate::common::{input_lines, try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::solver::{Solver, SolverResult};
 use std::collections::HashMap;
+use std::error::Error;
+use std::io::BufRead;
 
 /*
  Entry
 */
 
-pub fn part1(lines: Vec<String>) -> u64 {
-    let line_results: Vec<(Vec<Spring>, Vec<u64>)> = lines.iter().map(|l| parse_line(l)).collect();
+const DAY: u32 = 12;
+
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let line_results = parse_lines(lines)?;
 
     let result = solve_problem(line_results);
 
-    return result;
+    return Ok(result);
 }
-pub fn part2(lines: Vec<String>) -> u64 {
-    let line_results: Vec<(Vec<Spring>, Vec<u64>)> = lines.iter().map(|l| parse_line(l)).collect();
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let line_results = parse_lines(lines)?;
 
     let expanded_results = line_results
         .iter()
-        .map(|(springs, counts)| {
-            let mut msprings = springs.to_owned();
-            msprings.push(Spring::Unknown);
-            let l = msprings.len();
-            let rep_springs: Vec<Spring> = msprings.into_iter().cycle().take(l * 5 - 1).collect();
-
-            let mut mcounts = counts.to_owned();
-            let l = mcounts.len();
-            let rep_counts: Vec<u64> = mcounts.into_iter().cycle().take(l * 5).collect();
-            return (rep_springs, rep_counts);
-        })
+        .map(|(springs, counts)| unfold(springs, counts))
         .collect();
 
     let result = solve_problem(expanded_results);
 
-    return result;
+    return Ok(result);
+}
+
+/// `part1`, reading a row at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<u64, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        let (springs, counts) = parse_line(idx, l)?;
+        Ok(acc + arrangements(&springs, &counts))
+    });
+}
+
+/// `part2`, reading a row at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<u64, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        let (springs, counts) = parse_line(idx, l)?;
+        let (springs, counts) = unfold(&springs, &counts);
+        Ok(acc + arrangements(&springs, &counts))
+    });
+}
+
+/// Five copies of the row, with unknown springs between the copies
+fn unfold(springs: &Vec<Spring>, counts: &Vec<u64>) -> (Vec<Spring>, Vec<u64>) {
+    let mut msprings = springs.to_owned();
+    msprings.push(Spring::Unknown);
+    let l = msprings.len();
+    let rep_springs: Vec<Spring> = msprings.into_iter().cycle().take(l * 5 - 1).collect();
+
+    let mut mcounts = counts.to_owned();
+    let l = mcounts.len();
+    let rep_counts: Vec<u64> = mcounts.into_iter().cycle().take(l * 5).collect();
+    return (rep_springs, rep_counts);
 }
 
 /*
@@ -49,12 +82,12 @@ enum Spring {
 }
 
 impl Spring {
-    fn from_char(c: char) -> Spring {
+    fn from_char(c: char) -> Option<Spring> {
         match c {
-            '.' => Spring::Operational,
-            '#' => Self::Damaged,
-            '?' => Self::Unknown,
-            _ => panic!("Unrecognized character {}", c),
+            '.' => Some(Spring::Operational),
+            '#' => Some(Self::Damaged),
+            '?' => Some(Self::Unknown),
+            _ => None,
         }
     }
     fn to_char(&self) -> char {
@@ -91,11 +124,7 @@ impl Spring {
 fn solve_problem(line_results: Vec<(Vec<Spring>, Vec<u64>)>) -> u64 {
     let line_counts: Vec<u64> = line_results
         .iter()
-        .map(|(springs, count)| {
-            let mut visited: HashMap<(String, u64), u64> = HashMap::new();
-            let prefix: Vec<Spring> = vec![];
-            solve_line(springs, count, &prefix, &mut visited)
-        })
+        .map(|(springs, count)| arrangements(springs, count))
         .collect();
 
     // dbg!(&line_counts);
@@ -103,6 +132,12 @@ fn solve_problem(line_results: Vec<(Vec<Spring>, Vec<u64>)>) -> u64 {
     return line_counts.iter().sum();
 }
 
+fn arrangements(springs: &Vec<Spring>, counts: &Vec<u64>) -> u64 {
+    let mut visited: HashMap<(String, u64), u64> = HashMap::new();
+    let prefix: Vec<Spring> = vec![];
+    return solve_line(springs, counts, &prefix, &mut visited);
+}
+
 fn solve_line(
     springs: &Vec<Spring>,
     counts: &Vec<u64>,
@@ -212,49 +247,90 @@ fn debug_springs(springs: &Vec<Spring>) {
  Problem Ingestion
 */
 
-fn parse_line(l: &str) -> (Vec<Spring>, Vec<u64>) {
-    let ssres = space_separated(l);
+fn parse_lines(lines: &[&str]) -> Result<Vec<(Vec<Spring>, Vec<u64>)>, ParseError> {
+    return lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_line(idx, l))
+        .collect();
+}
 
-    let split_slin = match ssres {
-        Err(e) => panic!("Space Separated errored with: {}", e.to_string()),
-        Ok((_, l)) => l,
-    };
-    let springs = split_slin[0];
-    let counts_str = split_slin[1];
+fn parse_line(idx: usize, l: &str) -> Result<(Vec<Spring>, Vec<u64>), ParseError> {
+    let (springs, counts_str) = l
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(DAY, idx, 0, l, "expected '<springs> <counts>'"))?;
+
+    let springs_enums: Vec<Spring> = springs
+        .chars()
+        .enumerate()
+        .map(|(col, c)| {
+            Spring::from_char(c)
+                .ok_or_else(|| ParseError::new(DAY, idx, col, l, "expected '.', '#' or '?'"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    // Split by hand, so an empty count like the last in `1,` is an error rather than skipped
+    let counts: Vec<u64> = counts_str
+        .split(',')
+        .map(|s| match s.parse() {
+            Ok(0) => Err(ParseError::at(DAY, idx, l, s, "counts must be at least 1")),
+            Ok(n) => Ok(n),
+            Err(_) if s.is_empty() => Err(ParseError::at(DAY, idx, l, s, "expected a count")),
+            Err(_) => Err(ParseError::at(DAY, idx, l, s, "invalid count")),
+        })
+        .collect::<Result<_, _>>()?;
 
-    let springs_enums: Vec<Spring> = springs.chars().map(Spring::from_char).collect();
+    return Ok((springs_enums, counts));
+}
 
-    let csres = comma_separated(counts_str);
-    let count_slin = match csres {
-        Err(e) => panic!("Space Separated errored with: {}", e.to_string()),
-        Ok((_, l)) => l,
-    };
-    let counts: Vec<u64> = count_slin.iter().map(|s| s.parse().unwrap()).collect();
+pub struct Day12;
 
-    return (springs_enums, counts);
+impl Solver for Day12 {
+    fn day(&self) -> u32 {
+        12
+    }
+    fn name(&self) -> &'static str {
+        "Hot Springs"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "???.### 1,1,3\n.??..??...?##. 1,1,3\n?#?#?#?#?#?#?#? 1,3,1,6\n????.#...#... 4,1,1\n????.######..#####. 1,6,5\n?###???????? 3,2,1"
             .to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 21);
     }
     #[test]
     fn part1_test_tough_str() {
         let string_input = "??#?#????#..???????? 5,1,4,2".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 9);
     }
@@ -262,12 +338,46 @@ mod tests {
     #[test]
     fn part2_test() {
         let string_input = "???.### 1,1,3\n.??..??...?##. 1,1,3\n?#?#?#?#?#?#?#? 1,3,1,6\n????.#...#... 4,1,1\n????.######..#####. 1,6,5\n?###???????? 3,2,1";
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 525152);
     }
+
+    #[test]
+    fn bad_springs() {
+        let err = part1("???.### 1,1,3\n.??..x?...?##. 1,1,3").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
+
+        let err = part1("???.### 1,one,3").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 11));
+
+        assert!(part2("???.###").is_err());
+
+        let err = part1("??? 0").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (1, 5, "counts must be at least 1")
+        );
+
+        let err = part1("??? 1,").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (1, 7, "expected a count")
+        );
+
+        let err = part1("??? ").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+    }
+
+    #[test]
+    fn streaming() {
+        let test_input = "???.### 1,1,3\n.??..??...?##. 1,1,3\n?#?#?#?#?#?#?#? 1,3,1,6\n????.#...#... 4,1,1\n????.######..#####. 1,6,5\n?###???????? 3,2,1\n";
+
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 21);
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 525152);
+    }
 }
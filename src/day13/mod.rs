@@ -1,6 +1,9 @@
 #![allow(unused)]
 
-use crate::matrix::AoCMatrix;
+use crate::common::{blocks, input_lines, unsplit, NotImplementedError, ParseError};
+use crate::matrix::{AoCMatrix, LineView};
+use crate::parsers::{parse_char_grid, FromChar};
+use crate::solver::{Solver, SolverResult};
 
 use std::cmp::{max, min};
 use std::iter::zip;
@@ -11,33 +14,35 @@ enum Square {
     Rock,
 }
 
-impl Square {
-    fn new(c: char) -> Square {
+impl FromChar for Square {
+    fn from_char(c: char) -> Option<Square> {
         match c {
-            '#' => Square::Rock,
-            '.' => Square::Ash,
-            _ => panic!("Unrecognized character: {}", c),
+            '#' => Some(Square::Rock),
+            '.' => Some(Square::Ash),
+            _ => None,
         }
     }
 }
 
-pub fn part1(lines: Vec<String>) -> u64 {
-    let a = input_to_mats(&lines);
-    return a
+const DAY: u32 = 13;
+
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    let a = input_to_mats(input)?;
+    return Ok(a
         .into_iter()
         .map(|mat| reflect_and_check_cols(&mat, 0) + 100 * reflect_and_check_rows(&mat, 0))
-        .sum();
+        .sum());
 }
 
-pub fn part2(lines: Vec<String>) -> u64 {
-    let a = input_to_mats(&lines);
-    return a
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    let a = input_to_mats(input)?;
+    return Ok(a
         .into_iter()
         .map(|mat| reflect_and_check_cols(&mat, 1) + 100 * reflect_and_check_rows(&mat, 1))
-        .sum();
+        .sum());
 }
 
-fn reverse_and_check_vectors(vs: Vec<Vec<&Square>>, tgt: u64) -> u64 {
+fn reverse_and_check_vectors(vs: Vec<LineView<Square>>, tgt: u64) -> u64 {
     let l = vs.len();
 
     for r in 1..l {
@@ -46,7 +51,7 @@ fn reverse_and_check_vectors(vs: Vec<Vec<&Square>>, tgt: u64) -> u64 {
         let after = &vs[r..(r + delta)];
 
         if zip(before, after)
-            .map(|(a, b)| count_diff_vec(a.to_vec(), b.to_vec()))
+            .map(|(a, b)| count_diff_vec(*a, *b))
             .sum::<u64>()
             .eq(&tgt)
         {
@@ -58,53 +63,65 @@ fn reverse_and_check_vectors(vs: Vec<Vec<&Square>>, tgt: u64) -> u64 {
 }
 
 fn reflect_and_check_rows(m: &AoCMatrix<Square>, tgt: u64) -> u64 {
-    let rows = m.rows();
+    let rows = m.rows().collect();
     return reverse_and_check_vectors(rows, tgt);
 }
 
 fn reflect_and_check_cols(m: &AoCMatrix<Square>, tgt: u64) -> u64 {
-    let cols = m.cols();
-    return reverse_and_check_vectors(cols, tgt);
+    return reflect_and_check_rows(&m.transposed(), tgt);
 }
 
-fn compare_vec_eq<T: Eq>(a: Vec<&T>, b: Vec<&T>) -> bool {
-    return count_diff_vec(a, b).eq(&0);
+fn compare_vec_eq<T: Eq>(a: LineView<T>, b: LineView<T>) -> bool {
+    return a == b;
 }
 
-fn count_diff_vec<T: Eq>(a: Vec<&T>, b: Vec<&T>) -> u64 {
-    return zip(a, b)
-        .into_iter()
-        .map(|(a, b)| if a.eq(b) { 0 } else { 1 })
-        .sum();
+fn count_diff_vec<T: Eq>(a: LineView<T>, b: LineView<T>) -> u64 {
+    return zip(a, b).map(|(a, b)| if a.eq(b) { 0 } else { 1 }).sum();
 }
 
-fn input_to_mats(lines: &Vec<String>) -> impl Iterator<Item = AoCMatrix<Square>> + '_ {
-    return lines.split(|s| s.is_empty()).map(|lines| {
-        let square_vecs: Vec<Vec<Square>> = lines
-            .iter()
-            .map(|line| {
-                line.chars()
-                    .into_iter()
-                    .map(Square::new)
-                    .collect::<Vec<Square>>()
-            })
-            .collect();
-        AoCMatrix::from_rows(square_vecs)
-    });
+fn input_to_mats(input: &str) -> Result<Vec<AoCMatrix<Square>>, ParseError> {
+    return blocks(input)
+        .map(|(first_line, block)| {
+            let lines: Vec<&str> = input_lines(block).collect();
+            parse_char_grid(DAY, first_line, &lines)
+        })
+        .collect();
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    fn day(&self) -> u32 {
+        13
+    }
+    fn name(&self) -> &'static str {
+        "Point of Incidence"
+    }
+    /// Patterns are split apart, with their line numbers, while solving
+    fn split<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        return vec![input];
+    }
+    fn solve_split(&self, pieces: &[&str], part: u32) -> SolverResult {
+        let input = unsplit(pieces);
+        return match part {
+            1 => Ok(part1(&input)?.into()),
+            2 => Ok(part2(&input)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "#.##..##.\n..#.##.#.\n##......#\n##......#\n..#.##.#.\n..##..##.\n#.#.##.#.\n\n#...##..#\n#....#..#\n..##..###\n#####.##.\n#####.##.\n..##..###\n#....#..#"
                 .to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 405);
     }
@@ -113,10 +130,32 @@ mod tests {
     fn part2_test() {
         let string_input = "#.##..##.\n..#.##.#.\n##......#\n##......#\n..#.##.#.\n..##..##.\n#.#.##.#.\n\n#...##..#\n#....#..#\n..##..###\n#####.##.\n#####.##.\n..##..###\n#....#..#"
                 .to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 400);
     }
+
+    #[test]
+    fn bad_pattern() {
+        let string_input = "#.##\n..#.\n\n#..\n#.x";
+        let err = part1(string_input).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 3));
+
+        let string_input = "#.##\n..#.\n\n#..\n#.";
+        let err = part1(string_input).unwrap_err();
+        assert_eq!(err.message, "expected 3 columns, found 2");
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let string_input = "#.##..##.\r\n..#.##.#.\r\n##......#\r\n##......#\r\n..#.##.#.\r\n..##..##.\r\n#.#.##.#.\r\n\r\n\r\n#...##..#\r\n#....#..#\r\n..##..###\r\n#####.##.\r\n#####.##.\r\n..##..###\r\n#....#..#\r\n\r\n";
+        assert_eq!(part1(string_input).unwrap(), 405);
+        assert_eq!(part2(string_input).unwrap(), 400);
+
+        // Line numbers still count every blank line
+        let err = part1("#.##\r\n..#.\r\n\r\n\r\n#..\r\n#.x\r\n").unwrap_err();
+        assert_eq!((err.line, err.column), (6, 3));
+    }
 }
@@ -1,8 +1,10 @@
 use num::Integer;
 
-use crate::matrix::AoCMatrix;
+use crate::common::{with_lines, NotImplementedError, ParseError};
+use crate::matrix::{AoCMatrix, ColView, LineViewMut};
+use crate::parsers::{parse_char_grid, FromChar};
+use crate::solver::{Solver, SolverResult};
 use std::collections::HashMap;
-use std::iter::zip;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum Square {
@@ -11,13 +13,13 @@ enum Square {
     Space,
 }
 
-impl Square {
-    fn new(c: char) -> Square {
+impl FromChar for Square {
+    fn from_char(c: char) -> Option<Square> {
         match c {
-            '#' => Self::Cube,
-            'O' => Self::Round,
-            '.' => Self::Space,
-            _ => panic!("Unrecognized character {}", c),
+            '#' => Some(Self::Cube),
+            'O' => Some(Self::Round),
+            '.' => Some(Self::Space),
+            _ => None,
         }
     }
 }
@@ -36,32 +38,35 @@ impl std::fmt::Display for Square {
     }
 }
 
-pub fn part1(lines: Vec<String>) -> u64 {
-    let mut starting_square = input_to_mat(&lines);
+const DAY: u32 = 14;
+
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let mut starting_square = input_to_mat(lines)?;
 
     move_north(&mut starting_square);
 
-    for col in starting_square.cols().iter() {
-        let mut nv = vec![];
-        for r in col.iter() {
-            nv.push(**r);
-        }
-    }
+    return Ok(score_square(&starting_square));
+}
 
-    return score_square(&starting_square);
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part2_lines);
 }
 
-pub fn part2(lines: Vec<String>) -> u64 {
-    let mut starting_square = input_to_mat(&lines);
+pub fn part2_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let mut starting_square = input_to_mat(lines)?;
 
-    return spin_cycle(&mut starting_square, 1000000000);
+    return Ok(spin_cycle(&mut starting_square, 1000000000));
 }
 
 fn score_square(sq: &AoCMatrix<Square>) -> u64 {
-    return sq.cols().iter().map(|c| score(c.to_vec())).sum();
+    return sq.cols().map(score).sum();
 }
 
-fn score(col: Vec<&Square>) -> u64 {
+fn score(col: ColView<Square>) -> u64 {
     let l = col.len() as u64;
     return col
         .iter()
@@ -109,106 +114,74 @@ fn spin_cycle(sq: &mut AoCMatrix<Square>, i: u64) -> u64 {
 }
 
 fn move_north(sq: &mut AoCMatrix<Square>) {
-    sq.cols_mut().iter_mut().for_each(move_to_start);
+    for c in 0..sq.n_cols() {
+        move_to_start(sq.col_mut(c));
+    }
 }
 
 fn move_south(sq: &mut AoCMatrix<Square>) {
-    sq.cols_mut().iter_mut().for_each(|c| {
-        c.reverse();
-        move_to_start(c)
-    });
+    for c in 0..sq.n_cols() {
+        move_to_start(sq.col_mut(c).rev());
+    }
 }
 
 fn move_west(sq: &mut AoCMatrix<Square>) {
-    sq.rows_mut().iter_mut().for_each(move_to_start);
+    sq.rows_mut().for_each(move_to_start);
 }
 
 fn move_east(sq: &mut AoCMatrix<Square>) {
-    sq.rows_mut().iter_mut().for_each(|r| {
-        r.reverse();
-        move_to_start(r)
-    });
-}
-
-fn move_to_start(col: &mut Vec<&mut Square>) -> () {
-    let mut new_vec: Vec<Square> = vec![];
-    let mut i = 0;
-    loop {
-        if i >= col.len() {
-            break;
-        }
-        let entry = col[i].clone();
-
-        let next_character = match entry {
-            Square::Space => {
-                let next_cube = col[i..].iter().position(|s| (**s).eq(&Square::Cube));
-                let next_round = col[i..].iter().position(|s| (**s).eq(&Square::Round));
-
-                match next_round {
-                    None => Square::Space,
-                    Some(round_idx) => match next_cube {
-                        None => Square::Round,
-                        Some(cube_idx) => {
-                            if round_idx < cube_idx {
-                                Square::Round
-                            } else {
-                                entry
-                            }
-                        }
-                    },
-                }
-            }
-            Square::Round => Square::Round,
-            Square::Cube => Square::Cube,
-        };
-        match next_character {
+    sq.rows_mut().for_each(|r| move_to_start(r.rev()));
+}
+
+/// Roll every round rock towards index 0 until it hits a cube, another round rock or the edge
+fn move_to_start(mut line: LineViewMut<Square>) {
+    // Where the next round rock will come to rest
+    let mut free = 0;
+    for i in 0..line.len() {
+        match line[i] {
+            Square::Cube => free = i + 1,
             Square::Round => {
-                if next_character != entry {
-                    let next_round = col[i..]
-                        .iter()
-                        .position(|s| (**s).eq(&Square::Round))
-                        .unwrap();
-                    *col[i + next_round] = Square::Space;
-                }
+                line.swap(free, i);
+                free += 1;
             }
-            _ => (),
-        };
-        new_vec.push(next_character);
-
-        i += 1;
+            Square::Space => (),
+        }
     }
+}
 
-    // Copy the new vector into the old vector
-    for (col_sq, new_sq) in zip(col, new_vec) {
-        **col_sq = new_sq;
-    }
+fn input_to_mat(lines: &[&str]) -> Result<AoCMatrix<Square>, ParseError> {
+    return parse_char_grid(DAY, 0, lines);
 }
 
-fn input_to_mat(lines: &Vec<String>) -> AoCMatrix<Square> {
-    let square_vecs: Vec<Vec<Square>> = lines
-        .iter()
-        .map(|line| {
-            line.chars()
-                .into_iter()
-                .map(Square::new)
-                .collect::<Vec<Square>>()
-        })
-        .collect();
-    return AoCMatrix::from_rows(square_vecs);
+pub struct Day14;
+
+impl Solver for Day14 {
+    fn day(&self) -> u32 {
+        14
+    }
+    fn name(&self) -> &'static str {
+        "Parabolic Reflector Dish"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "O....#....\nO.OO#....#\n.....##...\nOO.#O....O\n.O.....O#.\nO.#..O.#.#\n..O..#O..O\n.......O..\n#....###..\n#OO..#...."
             .to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 136);
     }
@@ -217,9 +190,9 @@ mod tests {
     fn part2_test() {
         let string_input = "O....#....\nO.OO#....#\n.....##...\nOO.#O....O\n.O.....O#.\nO.#..O.#.#\n..O..#O..O\n.......O..\n#....###..\n#OO..#...."
             .to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 64);
     }
@@ -1,26 +1,77 @@
+use crate::common::{try_fold_records, with_lines, NotImplementedError, ParseError};
+use crate::solver::{Solver, SolverResult};
 use nom::FindToken;
 use std::collections::HashMap;
+use std::error::Error;
+use std::io::BufRead;
 
 /*
  Entrypoints
 */
 
-pub fn part1(lines: Vec<String>) -> usize {
-    let in_str = &lines[0];
+const DAY: u32 = 15;
 
-    return in_str.split(',').map(my_hash).sum();
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part1_lines);
 }
 
-pub fn part2(lines: Vec<String>) -> usize {
-    let in_str = &lines[0];
-    let cmds = in_str.split(',').map(Command::new);
-    let lens_boxes = fill_lens_boxes(cmds);
+pub fn part1_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let in_str = first_line(lines)?;
 
-    return lens_boxes
-        .iter()
-        .enumerate()
-        .map(|(idx, lb)| lb.score(idx))
-        .sum::<usize>();
+    return Ok(in_str.split(',').map(my_hash).sum());
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let in_str = first_line(lines)?;
+    let cmds = in_str
+        .split(',')
+        .map(|cmd| Command::new(in_str, cmd))
+        .collect::<Result<Vec<Command>, ParseError>>()?;
+    let lens_boxes = fill_lens_boxes(cmds.into_iter());
+
+    return Ok(focusing_power(&lens_boxes));
+}
+
+/// `part1`, reading a step at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<usize, Box<dyn Error>> {
+    let (total, steps) = try_fold_records(reader, b',', (0, 0), |(total, steps), _, step| {
+        Ok((total + my_hash(step), steps + 1))
+    })?;
+
+    return match steps {
+        0 => Err(no_sequence().into()),
+        _ => Ok(total),
+    };
+}
+
+/// `part2`, reading a step at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<usize, Box<dyn Error>> {
+    let boxes: Vec<_> = (0..256).map(|_| LensBox::new()).collect();
+    let (lens_boxes, steps) =
+        try_fold_records(reader, b',', (boxes, 0), |(mut boxes, steps), _, step| {
+            apply_command(&mut boxes, Command::new(step, step)?);
+            Ok((boxes, steps + 1))
+        })?;
+
+    return match steps {
+        0 => Err(no_sequence().into()),
+        _ => Ok(focusing_power(&lens_boxes)),
+    };
+}
+
+fn first_line<'a>(lines: &[&'a str]) -> Result<&'a str, ParseError> {
+    return match lines.first() {
+        Some(l) if !l.is_empty() => Ok(*l),
+        _ => Err(no_sequence()),
+    };
+}
+
+fn no_sequence() -> ParseError {
+    return ParseError::new(DAY, 0, 0, "", "expected an initialization sequence");
 }
 
 /*
@@ -35,19 +86,22 @@ enum Command<'a> {
     Remove(&'a str),
 }
 
-impl Command<'_> {
-    fn new(input: &str) -> Command {
+impl<'a> Command<'a> {
+    /// Parse a single step of the sequence, `input`, which is a slice of the whole `line`
+    fn new(line: &str, input: &'a str) -> Result<Command<'a>, ParseError> {
         let insertion = input.find_token('=');
 
         return if insertion {
-            let mut splits = input.split('=').into_iter();
-            let box_id = splits.next().unwrap();
-            let focal_length = splits.next().unwrap().parse().unwrap();
-            Command::Insert(box_id, focal_length)
+            let (box_id, focal_length) = input.split_once('=').unwrap();
+            let focal_length = focal_length
+                .parse()
+                .map_err(|_| ParseError::at(DAY, 0, line, focal_length, "invalid focal length"))?;
+            Ok(Command::Insert(box_id, focal_length))
         } else {
-            let mut splits = input.split('-');
-            let box_id = splits.next().unwrap();
-            Command::Remove(box_id)
+            match input.strip_suffix('-') {
+                Some(box_id) => Ok(Command::Remove(box_id)),
+                None => Err(ParseError::at(DAY, 0, line, input, "expected '=' or '-'")),
+            }
         };
     }
     fn get_target_idx(&self) -> usize {
@@ -142,28 +196,58 @@ pub fn my_hash(token: &str) -> usize {
 fn fill_lens_boxes<'a>(cmds: impl Iterator<Item = Command<'a>>) -> Vec<LensBox> {
     let mut boxes: Vec<_> = (0..256).map(|_| LensBox::new()).collect();
 
-    cmds.for_each(|cmd| {
-        let tgt = cmd.get_target_idx();
-        let target_box = &mut boxes[tgt];
-        match cmd {
-            Command::Insert(tgt, fl) => {
-                target_box.insert((tgt, fl));
-                ()
-            }
-            Command::Remove(tgt) => {
-                target_box.remove(tgt);
-                ()
-            }
-        }
-    });
+    cmds.for_each(|cmd| apply_command(&mut boxes, cmd));
 
-    return boxes.into();
+    return boxes;
+}
+
+fn apply_command(boxes: &mut [LensBox], cmd: Command) {
+    let target_box = &mut boxes[cmd.get_target_idx()];
+    match cmd {
+        Command::Insert(tgt, fl) => target_box.insert((tgt, fl)),
+        Command::Remove(tgt) => target_box.remove(tgt),
+    }
+}
+
+fn focusing_power(boxes: &[LensBox]) -> usize {
+    return boxes
+        .iter()
+        .enumerate()
+        .map(|(idx, lb)| lb.score(idx))
+        .sum::<usize>();
+}
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    fn day(&self) -> u32 {
+        15
+    }
+    fn name(&self) -> &'static str {
+        "Lens Library"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
     use std::iter::zip;
 
     #[test]
@@ -187,9 +271,9 @@ mod tests {
     #[test]
     fn part1_test() {
         let string_input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 1320);
     }
@@ -197,10 +281,20 @@ mod tests {
     #[test]
     fn part2_test() {
         let string_input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 145);
     }
+
+    #[test]
+    fn streaming() {
+        let string_input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7\n";
+
+        assert_eq!(part1_stream(string_input.as_bytes()).unwrap(), 1320);
+        assert_eq!(part2_stream(string_input.as_bytes()).unwrap(), 145);
+        assert!(part1_stream("".as_bytes()).is_err());
+        assert!(part2_stream("rn=1,cm".as_bytes()).is_err());
+    }
 }
@@ -1,7 +1,10 @@
 #![allow(unused)]
 
-use crate::matrix::AoCMatrix;
-use std::collections::HashSet;
+use crate::common::{input_lines, with_lines, NotImplementedError, ParseError};
+use crate::matrix::{AoCMatrix, Direction};
+use crate::parsers::{parse_char_grid, FromChar};
+use crate::search::bfs;
+use crate::solver::{Solver, SolverResult};
 use std::iter::zip;
 
 /*
@@ -20,15 +23,15 @@ enum Space {
     EW,
 }
 
-impl Space {
-    fn new(c: char) -> Space {
+impl FromChar for Space {
+    fn from_char(c: char) -> Option<Space> {
         match c {
-            '.' => Space::Empty,
-            '/' => Space::SW_NE,
-            '\\' => Space::NW_SE,
-            '|' => Space::NS,
-            '-' => Space::EW,
-            _ => panic!("Unrecognized character: {}", c),
+            '.' => Some(Space::Empty),
+            '/' => Some(Space::SW_NE),
+            '\\' => Some(Space::NW_SE),
+            '|' => Some(Space::NS),
+            '-' => Some(Space::EW),
+            _ => None,
         }
     }
 }
@@ -44,64 +47,33 @@ impl std::fmt::Display for Space {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Direction {
-    fn next_pos(&self, cpos: (usize, usize)) -> Option<(usize, usize)> {
-        match self {
-            Direction::North => {
-                if cpos.0 == 0 {
-                    None
-                } else {
-                    Some((cpos.0 - 1, cpos.1))
-                }
-            }
-            Direction::South => Some((cpos.0 + 1, cpos.1)),
-            Direction::East => Some((cpos.0, cpos.1 + 1)),
-            Direction::West => {
-                if cpos.1 == 0 {
-                    None
-                } else {
-                    Some((cpos.0, cpos.1 - 1))
-                }
-            }
-        }
-    }
-}
-
-impl std::fmt::Display for Direction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Direction::North => write!(f, "↑"),
-            Direction::South => write!(f, "↓"),
-            Direction::East => write!(f, "→"),
-            Direction::West => write!(f, "←"),
-        }
-    }
-}
-
 /*
     Entry
 */
 
-pub fn part1(lines: Vec<String>) -> usize {
-    let (board, mut state) = parse_board(lines);
+const DAY: u32 = 16;
 
-    return run_from_start(((0, 0), Direction::East), &board, &mut state);
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part1_lines);
 }
-pub fn part2(lines: Vec<String>) -> usize {
-    let (board, mut state) = parse_board(lines);
+
+pub fn part1_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let board = parse_board(lines)?;
+
+    return Ok(run_from_start(((0, 0), Direction::East), &board));
+}
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let board = parse_board(lines)?;
     let starts = generate_starts(&board);
 
-    let results = run_from_starts(&starts, &board, &state);
+    let results = run_from_starts(&starts, &board);
 
-    return *results.iter().max().unwrap();
+    // The board is never empty, so there's always at least one start
+    return Ok(*results.iter().max().unwrap());
 }
 
 /*
@@ -122,61 +94,31 @@ fn generate_starts(board: &AoCMatrix<Space>) -> Vec<BEAMSTATE> {
     return bs;
 }
 
-fn run_from_starts(
-    starts: &Vec<BEAMSTATE>,
-    board: &AoCMatrix<Space>,
-    state: &AoCMatrix<bool>,
-) -> Vec<usize> {
+fn run_from_starts(starts: &Vec<BEAMSTATE>, board: &AoCMatrix<Space>) -> Vec<usize> {
     return starts
         .iter()
-        .map(move |s| {
-            let mut state_copy = state.clone();
-            run_from_start(*s, &board, &mut state_copy)
-        })
+        .map(|s| run_from_start(*s, &board))
         .collect::<Vec<usize>>();
 }
 
-fn run_from_start(
-    start: BEAMSTATE,
-    board: &AoCMatrix<Space>,
-    mut state: &mut AoCMatrix<bool>,
-) -> usize {
-    state[start.0] = true;
-    let mut cmd_vec = vec![start];
-
-    let mut visited = HashSet::new();
+/// Number of squares energized by a beam entering the board at `start`. The start itself is
+///     already on the board, heading in its direction, so its square is energized before moving.
+fn run_from_start(start: BEAMSTATE, board: &AoCMatrix<Space>) -> usize {
+    let beams = bfs([start], |beam| process_step(*beam, board));
 
-    while let Some((cur_pos, dir)) = cmd_vec.pop() {
-        let new_cmds = process_step((cur_pos, dir), &board, &mut state, &mut visited);
-        for c in new_cmds {
-            cmd_vec.push(c);
-        }
+    let mut state = AoCMatrix::filled_matrix(false, board.n_rows(), board.n_cols());
+    for ((pos, _), _) in beams.reached_states() {
+        state[*pos] = true;
     }
     return state.get_data().iter().map(|b| *b as usize).sum();
 }
 
-fn process_step(
-    (cur_pos, dir): BEAMSTATE,
-    board: &AoCMatrix<Space>,
-    state: &mut AoCMatrix<bool>,
-    processed: &mut HashSet<BEAMSTATE>,
-) -> Vec<BEAMSTATE> {
-    if processed.contains(&(cur_pos, dir)) {
-        return vec![];
-    } else {
-        processed.insert((cur_pos, dir));
-    }
-
-    // Unwrap Nones with a default value outside of the matrix
-    let next_pos = dir
-        .next_pos(cur_pos)
-        .unwrap_or((board.n_rows() + 1, board.n_cols() + 1));
-
-    if !board.in_mat(next_pos) {
-        return vec![];
-    }
+fn process_step((cur_pos, dir): BEAMSTATE, board: &AoCMatrix<Space>) -> Vec<BEAMSTATE> {
+    let next_pos = match board.step(cur_pos, dir, 1) {
+        Some(p) => p,
+        None => return vec![],
+    };
 
-    state[next_pos] = true;
     let new_space = board[next_pos];
 
     return match new_space {
@@ -212,26 +154,26 @@ fn process_step(
     Parsing
 */
 
-fn parse_board(lines: Vec<String>) -> (AoCMatrix<Space>, AoCMatrix<bool>) {
-    let board = AoCMatrix::from_rows(
-        lines
-            .into_iter()
-            .map(|l| {
-                l.chars()
-                    .into_iter()
-                    .map(|c| Space::new(c))
-                    .collect::<Vec<Space>>()
-            })
-            .collect::<Vec<Vec<Space>>>(),
-    );
-
-    let rows = board.n_rows();
-    let cols = board.n_cols();
-    let init_rows = vec![vec![false; cols]; rows];
-
-    let state = AoCMatrix::from_rows(init_rows);
-
-    return (board, state);
+fn parse_board(lines: &[&str]) -> Result<AoCMatrix<Space>, ParseError> {
+    return parse_char_grid(DAY, 0, lines);
+}
+
+pub struct Day16;
+
+impl Solver for Day16 {
+    fn day(&self) -> u32 {
+        16
+    }
+    fn name(&self) -> &'static str {
+        "The Floor Will Be Lava"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 /*
@@ -240,14 +182,13 @@ fn parse_board(lines: Vec<String>) -> (AoCMatrix<Space>, AoCMatrix<bool>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = ".|...\\....\n|.-.\\.....\n.....|-...\n........|.\n..........\n.........\\\n..../.\\\\..\n.-.-/..|..\n.|....-|.\\\n..//.|....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 46);
     }
@@ -255,9 +196,9 @@ mod tests {
     #[test]
     fn part2_test() {
         let string_input = ".|...\\....\n|.-.\\.....\n.....|-...\n........|.\n..........\n.........\\\n..../.\\\\..\n.-.-/..|..\n.|....-|.\\\n..//.|....".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 51);
     }
@@ -1,10 +1,18 @@
-use crate::matrix::AoCMatrix;
-use std::collections::HashSet;
-
 use std::cmp::Reverse;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
+use std::error;
+
+use clap::Args;
+
+use crate::common::{
+    input_lines, with_lines, InvalidCrucibleError, NotImplementedError, ParseError,
+    UnreachableError,
+};
+use crate::matrix::{AoCMatrix, Direction};
+use crate::parsers::parse_grid;
+use crate::search::dijkstra;
+use crate::solver::{Solver, SolverResult};
 
-type HEAP = BinaryHeap<Reverse<DijkstraState>>;
 type BOARD = AoCMatrix<usize>;
 type POSITION = (usize, usize);
 
@@ -12,8 +20,10 @@ type POSITION = (usize, usize);
     Types
 */
 
+/// A crucible having just moved onto `position`, heading in `direction`, `current` squares into a
+///     straight run which must be between `min` and `max` long
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-struct Move {
+pub struct Move {
     position: POSITION,
     direction: Direction,
     min: usize,
@@ -46,30 +56,11 @@ impl Move {
             max,
         };
     }
-    fn step(&self) -> Option<Move> {
+    fn step(&self, board: &BOARD) -> Option<Move> {
         if self.current == self.max {
             return None;
         }
-        match self.direction {
-            Direction::North => {
-                if self.position.0 == 0 {
-                    return None;
-                }
-            }
-            Direction::West => {
-                if self.position.1 == 0 {
-                    return None;
-                }
-            }
-            _ => (),
-        };
-        let cur_pos = self.position;
-        let new_pos = match self.direction {
-            Direction::North => (cur_pos.0 - 1, cur_pos.1),
-            Direction::South => (cur_pos.0 + 1, cur_pos.1),
-            Direction::East => (cur_pos.0, cur_pos.1 + 1),
-            Direction::West => (cur_pos.0, cur_pos.1 - 1),
-        };
+        let new_pos = board.step(self.position, self.direction, 1)?;
 
         return Some(Move::new(
             self.current + 1,
@@ -79,156 +70,537 @@ impl Move {
             self.max,
         ));
     }
-    fn left(&self) -> Option<Move> {
+    /// Set off in a new direction, which is only allowed once the current run is long enough
+    fn turn(&self, direction: Direction, board: &BOARD) -> Option<Move> {
         return if self.can_stop() {
-            Move::new_from_zero(self.position, self.direction.left(), self.min, self.max).step()
+            Move::new_from_zero(self.position, direction, self.min, self.max).step(board)
         } else {
             None
         };
     }
-    fn right(&self) -> Option<Move> {
-        return if self.can_stop() {
-            Move::new_from_zero(self.position, self.direction.right(), self.min, self.max).step()
-        } else {
-            None
-        };
+    fn left(&self, board: &BOARD) -> Option<Move> {
+        return self.turn(self.direction.left(), board);
+    }
+    fn right(&self, board: &BOARD) -> Option<Move> {
+        return self.turn(self.direction.right(), board);
+    }
+    fn u_turn(&self, board: &BOARD) -> Option<Move> {
+        return self.turn(self.direction.reverse(), board);
     }
     fn can_stop(&self) -> bool {
         return self.min <= self.current;
     }
+    pub fn position(&self) -> POSITION {
+        self.position
+    }
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Clone, Copy, Ord, PartialOrd)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+/// The crucible's cheapest way across the board, starting with the move it sets off in
+pub struct Route {
+    pub heat_loss: usize,
+    pub moves: Vec<Move>,
+    board: BOARD,
 }
 
-impl Direction {
-    fn left(&self) -> Direction {
-        match self {
-            Direction::North => Direction::West,
-            Direction::South => Direction::East,
-            Direction::East => Direction::North,
-            Direction::West => Direction::South,
+impl std::fmt::Display for Route {
+    /// The board's heat digits, with each square the crucible moved onto replaced by an arrow
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut squares = AoCMatrix::from_fn(self.board.n_rows(), self.board.n_cols(), |pos| {
+            char::from_digit(self.board[pos] as u32, 10).unwrap()
+        });
+        // The starting move hasn't gone anywhere yet
+        for mv in self.moves.iter().filter(|mv| mv.current > 0) {
+            squares[mv.position] = match mv.direction {
+                Direction::North => '^',
+                Direction::South => 'v',
+                Direction::East => '>',
+                Direction::West => '<',
+            };
         }
+        write!(f, "{}", squares)
+    }
+}
+
+/// How a crucible is allowed to move, and where from and to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrucibleRules {
+    /// Squares it has to move in a straight line before it can turn or stop
+    pub min_run: usize,
+    /// Squares it can move in a straight line before it has to turn
+    pub max_run: usize,
+    pub start: POSITION,
+    /// Any of these will do. The bottom right corner when empty
+    pub targets: Vec<POSITION>,
+    /// Whether it can turn straight back the way it came, under the same rules as turning
+    pub u_turns: bool,
+    /// Heat lost on entering a square is multiplied by the multiplier for the direction of travel,
+    ///     1 for directions not given
+    pub cost_multipliers: HashMap<Direction, usize>,
+}
+
+impl CrucibleRules {
+    pub fn new(min_run: usize, max_run: usize) -> CrucibleRules {
+        return CrucibleRules {
+            min_run,
+            max_run,
+            start: (0, 0),
+            targets: vec![],
+            u_turns: false,
+            cost_multipliers: HashMap::new(),
+        };
+    }
+    pub fn part1() -> CrucibleRules {
+        return CrucibleRules::new(1, 3);
+    }
+    pub fn part2() -> CrucibleRules {
+        return CrucibleRules::new(4, 10);
+    }
+    fn multiplier(&self, direction: Direction) -> usize {
+        return *self.cost_multipliers.get(&direction).unwrap_or(&1);
+    }
+    /// Every multiplier, looked up by `direction_index` so searches don't hash on each step
+    fn multipliers(&self) -> [usize; 4] {
+        return Direction::ALL.map(|d| self.multiplier(d));
+    }
+    fn targets_on(&self, board: &BOARD) -> Vec<POSITION> {
+        return if self.targets.is_empty() {
+            vec![(board.n_rows() - 1, board.n_cols() - 1)]
+        } else {
+            self.targets.clone()
+        };
+    }
+
+    /// `max_run`, cut down to the longest straight line the board has room for
+    fn longest_run_on(&self, board: &BOARD) -> usize {
+        return self.max_run.min(board.n_rows().max(board.n_cols()));
     }
-    fn right(&self) -> Direction {
-        match self {
-            Direction::North => Direction::East,
-            Direction::South => Direction::West,
-            Direction::East => Direction::South,
-            Direction::West => Direction::North,
+
+    fn validate(&self, board: &BOARD) -> Result<(), InvalidCrucibleError> {
+        if self.min_run > self.max_run || self.max_run == 0 {
+            return Err(InvalidCrucibleError(format!(
+                "runs of {} to {} squares are impossible",
+                self.min_run, self.max_run
+            )));
+        }
+        for pos in [self.start].iter().chain(self.targets.iter()) {
+            if !board.in_mat(*pos) {
+                return Err(InvalidCrucibleError(format!(
+                    "{},{} is outside the {}x{} board",
+                    pos.0,
+                    pos.1,
+                    board.n_rows(),
+                    board.n_cols()
+                )));
+            }
+        }
+
+        let hottest = *board.get_data().iter().max().unwrap_or(&0);
+        let multiplier = *self.multipliers().iter().max().unwrap();
+        let worst_step = hottest.checked_mul(multiplier).ok_or_else(|| {
+            InvalidCrucibleError(format!(
+                "a multiplier of {} overflows the heat lost on a square of {}",
+                multiplier, hottest
+            ))
+        })?;
+        // No route goes through the same state twice. A board with too many states to count is
+        //     turned down by the search itself.
+        let runs = self.longest_run_on(board) + 1;
+        if let Some(states) = state_count(board.n_rows(), board.n_cols(), runs) {
+            if states.checked_mul(worst_step).is_none() {
+                return Err(InvalidCrucibleError(format!(
+                    "losing up to {} heat a square, a route across the {}x{} board could overflow",
+                    worst_step,
+                    board.n_rows(),
+                    board.n_cols()
+                )));
+            }
         }
+        return Ok(());
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct DijkstraState {
-    /// val_to_here is the total walk to here **including the current square**
-    /// position is the current index
-    /// move_to_here is the movement that brought us into this square
-    val_to_here: usize,
-    current_move: Move,
+/// Command line overrides for day 17's rules, applied on top of the part's own rules
+#[derive(Args, Clone, Debug, Default)]
+#[command(next_help_heading = "Day 17 crucible rules")]
+pub struct CrucibleArgs {
+    /// shortest straight run before turning or stopping
+    #[arg(long, value_name = "N")]
+    pub crucible_min: Option<usize>,
+
+    /// longest straight run before having to turn
+    #[arg(long, value_name = "N")]
+    pub crucible_max: Option<usize>,
+
+    /// cell to start from, as row,col counting from 0
+    #[arg(long, value_name = "ROW,COL", value_parser = parse_cell)]
+    pub crucible_start: Option<POSITION>,
+
+    /// cell to finish on, as row,col. May be given several times to accept any of them
+    #[arg(long, value_name = "ROW,COL", value_parser = parse_cell)]
+    pub crucible_target: Vec<POSITION>,
+
+    /// allow turning straight back
+    #[arg(long)]
+    pub crucible_u_turns: bool,
+
+    /// multiply heat lost moving in a direction, e.g. N=2. May be given for each of N, S, E and W
+    #[arg(long, value_name = "DIR=N", value_parser = parse_multiplier)]
+    pub crucible_cost: Vec<(Direction, usize)>,
 }
 
-impl DijkstraState {
-    fn new(val_to_here: usize, move_to_here: Move) -> DijkstraState {
-        return DijkstraState {
-            val_to_here,
-            current_move: move_to_here,
-        };
+impl CrucibleArgs {
+    pub fn is_set(&self) -> bool {
+        return self.crucible_min.is_some()
+            || self.crucible_max.is_some()
+            || self.crucible_start.is_some()
+            || !self.crucible_target.is_empty()
+            || self.crucible_u_turns
+            || !self.crucible_cost.is_empty();
     }
+    pub fn apply(&self, mut rules: CrucibleRules) -> CrucibleRules {
+        rules.min_run = self.crucible_min.unwrap_or(rules.min_run);
+        rules.max_run = self.crucible_max.unwrap_or(rules.max_run);
+        rules.start = self.crucible_start.unwrap_or(rules.start);
+        if !self.crucible_target.is_empty() {
+            rules.targets = self.crucible_target.clone();
+        }
+        rules.u_turns |= self.crucible_u_turns;
+        rules
+            .cost_multipliers
+            .extend(self.crucible_cost.iter().copied());
+        return rules;
+    }
+}
+
+fn parse_cell(s: &str) -> Result<POSITION, InvalidCrucibleError> {
+    let invalid = || InvalidCrucibleError(format!("expected a cell like 3,4, found '{}'", s));
+
+    let (row, col) = s.split_once(',').ok_or_else(invalid)?;
+    let row = row.trim().parse().map_err(|_| invalid())?;
+    let col = col.trim().parse().map_err(|_| invalid())?;
+    return Ok((row, col));
+}
+
+fn parse_multiplier(s: &str) -> Result<(Direction, usize), InvalidCrucibleError> {
+    let invalid = || InvalidCrucibleError(format!("expected a multiplier like N=2, found '{}'", s));
+
+    let (dir, n) = s.split_once('=').ok_or_else(invalid)?;
+    let dir = match dir.trim() {
+        "N" => Direction::North,
+        "S" => Direction::South,
+        "E" => Direction::East,
+        "W" => Direction::West,
+        _ => return Err(invalid()),
+    };
+    let n = n.trim().parse().map_err(|_| invalid())?;
+    return Ok((dir, n));
+}
+
+/// Ways of finding the least heat loss, which all give the same answer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// The general search in `crate::search`, hashing every move. The only one to keep the route
+    Dijkstra,
+    /// The same search over a flat array of every state a crucible can be in
+    DenseDijkstra,
 }
 
 /*
     Entry
 */
 
-pub fn part1(lines: Vec<String>) -> usize {
-    let board = parse_input(&lines);
-    let mut heap = HEAP::new();
-    push_to_heap(
-        DijkstraState::new(0, Move::new_from_zero((0, 0), Direction::East, 1, 3)),
-        &mut heap,
-    );
+const DAY: u32 = 17;
+
+pub fn part1(input: &str) -> Result<usize, Box<dyn error::Error>> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<usize, Box<dyn error::Error>> {
+    let board = parse_input(lines)?;
+    return least_heat_loss(&board, &CrucibleRules::part1(), Strategy::DenseDijkstra);
+}
 
-    let tgt = (board.n_rows() - 1, board.n_cols() - 1);
+pub fn part2(input: &str) -> Result<usize, Box<dyn error::Error>> {
+    return with_lines(input, part2_lines);
+}
 
-    return dijkstra(&mut heap, &board, tgt);
+pub fn part2_lines(lines: &[&str]) -> Result<usize, Box<dyn error::Error>> {
+    let board = parse_input(lines)?;
+    return least_heat_loss(&board, &CrucibleRules::part2(), Strategy::DenseDijkstra);
 }
 
-pub fn part2(lines: Vec<String>) -> usize {
-    let board = parse_input(&lines);
-    let mut heap = HEAP::new();
-    push_to_heap(
-        DijkstraState::new(0, Move::new_from_zero((0, 0), Direction::East, 4, 10)),
-        &mut heap,
-    );
-    push_to_heap(
-        DijkstraState::new(0, Move::new_from_zero((0, 0), Direction::South, 4, 10)),
-        &mut heap,
-    );
+/// The route behind `part1`'s answer
+pub fn part1_route(input: &str) -> Result<Route, Box<dyn error::Error>> {
+    let lines: Vec<&str> = input_lines(input).collect();
+    let board = parse_input(&lines)?;
+    return Ok(best_route(board, &CrucibleRules::part1())?);
+}
 
-    let tgt = (board.n_rows() - 1, board.n_cols() - 1);
+/// The route behind `part2`'s answer
+pub fn part2_route(input: &str) -> Result<Route, Box<dyn error::Error>> {
+    let lines: Vec<&str> = input_lines(input).collect();
+    let board = parse_input(&lines)?;
+    return Ok(best_route(board, &CrucibleRules::part2())?);
+}
 
-    return dijkstra(&mut heap, &board, tgt);
+/// The cheapest route under any rules, which are checked against the board first
+pub fn route_with_rules(
+    input: &str,
+    rules: &CrucibleRules,
+) -> Result<Route, Box<dyn error::Error>> {
+    let lines: Vec<&str> = input_lines(input).collect();
+    let board = parse_input(&lines)?;
+    rules.validate(&board)?;
+    return Ok(best_route(board, rules)?);
+}
+
+/// The least heat loss under any rules, found with `strategy`
+pub fn heat_loss_with(
+    input: &str,
+    rules: &CrucibleRules,
+    strategy: Strategy,
+) -> Result<usize, Box<dyn error::Error>> {
+    return with_lines(input, |lines| heat_loss_on_lines(lines, rules, strategy));
+}
+
+pub fn heat_loss_on_lines(
+    lines: &[&str],
+    rules: &CrucibleRules,
+    strategy: Strategy,
+) -> Result<usize, Box<dyn error::Error>> {
+    let board = parse_input(lines)?;
+    rules.validate(&board)?;
+    return least_heat_loss(&board, rules, strategy);
 }
 
 /*
  Parsing, problem setup
 */
 
-fn parse_input(input: &Vec<String>) -> BOARD {
-    let row_ints = input
-        .iter()
-        .map(|r| {
-            r.chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect()
-        })
-        .collect();
-    return AoCMatrix::from_rows(row_ints);
+fn parse_input(input: &[&str]) -> Result<BOARD, ParseError> {
+    return parse_grid(DAY, 0, input, |c| c.to_digit(10).map(|d| d as usize));
+}
+
+/// Some boards (e.g. a single row too short for the ultra crucible to stop in) have no route
+fn unreachable_error(rules: &CrucibleRules, targets: Vec<POSITION>) -> UnreachableError {
+    return UnreachableError {
+        start: rules.start,
+        targets,
+    };
 }
 
 /*
  Business Logic
 */
 
-fn dijkstra(heap: &mut HEAP, board: &BOARD, target: POSITION) -> usize {
-    let mut visited: HashSet<Move> = HashSet::new();
-    while let Some(Reverse(state)) = heap.pop() {
-        let cmove = state.current_move;
-        let pos = cmove.position;
+/// Setting off counts as a turn, so the first run has to be long enough too
+fn start_moves(rules: &CrucibleRules) -> Vec<Move> {
+    return Direction::ALL
+        .into_iter()
+        .map(|d| Move::new_from_zero(rules.start, d, rules.min_run, rules.max_run))
+        .collect();
+}
+
+/// Every move the crucible can make next, with the heat lost on the square it enters
+fn next_moves<'a>(
+    cmove: &Move,
+    board: &'a BOARD,
+    rules: &CrucibleRules,
+    multipliers: [usize; 4],
+) -> impl Iterator<Item = (Move, usize)> + 'a {
+    let u_turn = if rules.u_turns {
+        cmove.u_turn(board)
+    } else {
+        None
+    };
+    return [
+        cmove.left(board),
+        cmove.right(board),
+        cmove.step(board),
+        u_turn,
+    ]
+    .into_iter()
+    .flatten()
+    .map(move |mv| {
+        let multiplier = multipliers[direction_index(mv.direction)];
+        (mv, board[mv.position] * multiplier)
+    });
+}
+
+/// Position of `direction` in `Direction::ALL`
+fn direction_index(direction: Direction) -> usize {
+    return match direction {
+        Direction::North => 0,
+        Direction::East => 1,
+        Direction::South => 2,
+        Direction::West => 3,
+    };
+}
+
+/// Cheapest route under `rules`, which must fit the board
+fn best_route(board: BOARD, rules: &CrucibleRules) -> Result<Route, UnreachableError> {
+    let targets = rules.targets_on(&board);
+    let multipliers = rules.multipliers();
+    let successors = |cmove: &Move| next_moves(cmove, &board, rules, multipliers);
+    let is_goal = |cmove: &Move| targets.contains(&cmove.position) && cmove.can_stop();
+
+    let search = dijkstra(start_moves(rules), successors, is_goal);
+
+    return match (search.goal_distance(), search.goal_path()) {
+        (Some(heat_loss), Some(moves)) => Ok(Route {
+            heat_loss,
+            moves,
+            board,
+        }),
+        _ => Err(unreachable_error(rules, targets)),
+    };
+}
 
-        if pos == target && cmove.can_stop() {
-            return state.val_to_here;
+/// Least heat loss under `rules`, which must fit the board
+fn least_heat_loss(
+    board: &BOARD,
+    rules: &CrucibleRules,
+    strategy: Strategy,
+) -> Result<usize, Box<dyn error::Error>> {
+    let targets = rules.targets_on(board);
+    let heat_loss = match strategy {
+        Strategy::Dijkstra => {
+            let multipliers = rules.multipliers();
+            let successors = |cmove: &Move| next_moves(cmove, board, rules, multipliers);
+            let is_goal = |cmove: &Move| targets.contains(&cmove.position) && cmove.can_stop();
+            dijkstra(start_moves(rules), successors, is_goal).goal_distance()
         }
-        let left = cmove.left();
-        let right = cmove.right();
-        let forward = cmove.step();
-
-        let mvs = [left, right, forward];
-
-        for opt_mv in mvs {
-            if let Some(mv) = opt_mv {
-                let new_pos = mv.position;
-                if board.in_mat(new_pos) && visited.get(&mv).is_none() {
-                    visited.insert(mv);
-                    let new_val = state.val_to_here + board[new_pos];
-                    push_to_heap(DijkstraState::new(new_val, mv), heap);
-                }
+        Strategy::DenseDijkstra => dense_search(board, rules, &targets)?,
+    };
+    return Ok(heat_loss.ok_or_else(|| unreachable_error(rules, targets))?);
+}
+
+/// Search with the best heat loss for every (row, col, direction, run length) kept in one flat
+///     array rather than hashing each `Move`, and the heap holding indices into it
+fn dense_search(
+    board: &BOARD,
+    rules: &CrucibleRules,
+    targets: &Vec<POSITION>,
+) -> Result<Option<usize>, InvalidCrucibleError> {
+    let runs = rules.longest_run_on(board) + 1;
+    let states = state_count(board.n_rows(), board.n_cols(), runs).ok_or_else(|| {
+        InvalidCrucibleError(format!(
+            "a {}x{} board with runs of up to {} has too many states to search",
+            board.n_rows(),
+            board.n_cols(),
+            runs - 1
+        ))
+    })?;
+
+    let multipliers = rules.multipliers();
+    let is_target = AoCMatrix::from_fn(board.n_rows(), board.n_cols(), |p| targets.contains(&p));
+
+    let index = |mv: &Move| {
+        let square = mv.position.0 * board.n_cols() + mv.position.1;
+        return (square * 4 + direction_index(mv.direction)) * runs + mv.current;
+    };
+    let state = |idx: usize| {
+        let (square, current) = (idx / runs, idx % runs);
+        let position = (square / 4 / board.n_cols(), square / 4 % board.n_cols());
+        let direction = Direction::ALL[square % 4];
+        return Move::new(current, position, direction, rules.min_run, rules.max_run);
+    };
+
+    // `validate` keeps every route's heat loss under usize::MAX, so it can stand for unreached
+    let mut best = vec![usize::MAX; states];
+    let mut heap = BinaryHeap::new();
+    for mv in start_moves(rules) {
+        best[index(&mv)] = 0;
+        heap.push(Reverse((0, index(&mv))));
+    }
+
+    while let Some(Reverse((heat_loss, idx))) = heap.pop() {
+        // A cheaper way into this state was found after this entry was pushed
+        if heat_loss > best[idx] {
+            continue;
+        }
+        let cmove = state(idx);
+        if is_target[cmove.position] && cmove.can_stop() {
+            return Ok(Some(heat_loss));
+        }
+        for (mv, cost) in next_moves(&cmove, board, rules, multipliers) {
+            let new_loss = heat_loss + cost;
+            let i = index(&mv);
+            if new_loss < best[i] {
+                best[i] = new_loss;
+                heap.push(Reverse((new_loss, i)));
             }
         }
     }
 
-    return 1;
+    return Ok(None);
+}
+
+/// Every (row, col, direction, run length) a crucible can be in, or None if that overflows
+fn state_count(rows: usize, cols: usize, runs: usize) -> Option<usize> {
+    return rows.checked_mul(cols)?.checked_mul(4)?.checked_mul(runs);
+}
+
+/// A pseudo-random board of heat digits, the same for the same seed, for benchmarks and tests
+pub fn generate_board(rows: usize, cols: usize, seed: u64) -> String {
+    // xorshift64, which gets stuck on a zero state
+    let mut state = seed.max(1);
+    let mut next_digit = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return char::from_digit((state % 9 + 1) as u32, 10).unwrap();
+    };
+    return (0..rows)
+        .map(|_| (0..cols).map(|_| next_digit()).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+pub struct Day17;
+
+impl Solver for Day17 {
+    fn day(&self) -> u32 {
+        17
+    }
+    fn name(&self) -> &'static str {
+        "Clumsy Crucible"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+}
+
+/// Day 17 with its rules overridden from the command line. Each part's own rules are the
+///     starting point, so e.g. only changing the target keeps the part's run lengths.
+pub struct CustomCrucible {
+    pub args: CrucibleArgs,
 }
 
-fn push_to_heap(state: DijkstraState, heap: &mut HEAP) {
-    heap.push(Reverse(state));
+impl Solver for CustomCrucible {
+    fn day(&self) -> u32 {
+        17
+    }
+    fn name(&self) -> &'static str {
+        "Clumsy Crucible"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        let rules = match part {
+            1 => CrucibleRules::part1(),
+            2 => CrucibleRules::part2(),
+            _ => return Err(NotImplementedError.into()),
+        };
+        let heat_loss =
+            heat_loss_on_lines(lines, &self.args.apply(rules), Strategy::DenseDijkstra)?;
+        return Ok(heat_loss.into());
+    }
 }
 
 /*
@@ -238,14 +610,13 @@ fn push_to_heap(state: DijkstraState, heap: &mut HEAP) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n4546657867536\n1438598798454\n4457876987766\n3637877979653\n4654967986887\n4564679986453\n1224686865563\n2546548887735\n4322674655533".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 102);
     }
@@ -253,9 +624,9 @@ mod tests {
     #[test]
     fn part2_test() {
         let string_input = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n4546657867536\n1438598798454\n4457876987766\n3637877979653\n4654967986887\n4564679986453\n1224686865563\n2546548887735\n4322674655533".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 94);
     }
@@ -264,10 +635,156 @@ mod tests {
     fn part2_test_extra() {
         let string_input =
             "111111111111\n999999999991\n999999999991\n999999999991\n999999999991".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 71);
     }
+
+    #[test]
+    fn route() {
+        let string_input = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n4546657867536\n1438598798454\n4457876987766\n3637877979653\n4654967986887\n4564679986453\n1224686865563\n2546548887735\n4322674655533".to_string();
+        let line_input = &string_input;
+
+        let route = part1_route(line_input).unwrap();
+
+        assert_eq!(route.heat_loss, 102);
+        assert_eq!(
+            route.to_string(),
+            "2>>34^>>>1323\n32v>>>35v>623\n325524565v>54\n3446585845v52\n4546657867v>6\n14385987984v4\n44578769877v6\n36378779796v>\n465496798688v\n456467998645v\n12246868655<v\n25465488877v5\n43226746555v>"
+        );
+        assert_eq!(route.moves[0].position(), (0, 0));
+        assert_eq!(route.moves.last().unwrap().position(), (12, 12));
+    }
+
+    #[test]
+    fn custom_rules() {
+        let string_input = "2413432311323\n3215453535623\n3255245654254\n3446585845452\n4546657867536\n1438598798454\n4457876987766\n3637877979653\n4654967986887\n4564679986453\n1224686865563\n2546548887735\n4322674655533".to_string();
+        let line_input = &string_input;
+        let heat_loss = |input: &str, rules: &CrucibleRules| {
+            route_with_rules(input, rules).map(|r| r.heat_loss)
+        };
+
+        assert_eq!(heat_loss(&line_input, &CrucibleRules::part2()).unwrap(), 94);
+
+        let mut rules = CrucibleRules::part1();
+        rules.start = (0, 1);
+        rules.targets = vec![(0, 0)];
+        assert_eq!(heat_loss(&line_input, &rules).unwrap(), 2);
+
+        let mut rules = CrucibleRules::part1();
+        rules.cost_multipliers = Direction::ALL.into_iter().map(|d| (d, 2)).collect();
+        assert_eq!(heat_loss(&line_input, &rules).unwrap(), 204);
+
+        // Getting back to the start of a single row needs a U-turn
+        let row = "12345";
+        let mut rules = CrucibleRules::new(2, 2);
+        rules.targets = vec![(0, 0)];
+        assert!(heat_loss(&row, &rules).is_err());
+        rules.u_turns = true;
+        assert_eq!(heat_loss(&row, &rules).unwrap(), 8);
+
+        rules.start = (1, 0);
+        let err = heat_loss(&row, &rules).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid crucible rules: 1,0 is outside the 1x5 board"
+        );
+
+        let args = CrucibleArgs {
+            crucible_max: Some(10),
+            crucible_cost: vec![parse_multiplier("E=3").unwrap()],
+            ..Default::default()
+        };
+        let rules = args.apply(CrucibleRules::part1());
+        assert_eq!((rules.min_run, rules.max_run), (1, 10));
+        assert_eq!(rules.multiplier(Direction::East), 3);
+        assert_eq!(parse_cell("3, 4").unwrap(), (3, 4));
+
+        // Each step's heat loss is past u32, but the route still gets there
+        let mut rules = CrucibleRules::new(1, 4);
+        rules.cost_multipliers = [(Direction::East, 1 << 33)].into();
+        for strategy in [Strategy::Dijkstra, Strategy::DenseDijkstra] {
+            assert_eq!(heat_loss_with(&row, &rules, strategy).unwrap(), 14 << 33);
+        }
+        rules.cost_multipliers = [(Direction::East, usize::MAX / 2)].into();
+        let err = heat_loss_with(&row, &rules, Strategy::DenseDijkstra).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Invalid crucible rules: a multiplier of {} overflows the heat lost on a square of 5",
+                usize::MAX / 2
+            )
+        );
+        rules.cost_multipliers = [(Direction::East, usize::MAX / 64)].into();
+        assert!(heat_loss_with(&row, &rules, Strategy::Dijkstra)
+            .unwrap_err()
+            .downcast::<InvalidCrucibleError>()
+            .is_ok());
+        assert!(parse_cell("3").is_err());
+    }
+
+    #[test]
+    fn strategies_agree() {
+        let strategies = [Strategy::Dijkstra, Strategy::DenseDijkstra];
+
+        let mut weighted = CrucibleRules::part2();
+        weighted.cost_multipliers = [(Direction::North, 3), (Direction::West, 2)].into();
+        weighted.targets = vec![(0, 24), (24, 0)];
+        let mut u_turns = CrucibleRules::part1();
+        u_turns.u_turns = true;
+        u_turns.start = (12, 12);
+
+        for seed in 1..4 {
+            let board = generate_board(25, 25, seed);
+            for rules in [
+                CrucibleRules::part1(),
+                CrucibleRules::part2(),
+                weighted.clone(),
+                u_turns.clone(),
+            ] {
+                let answers: Vec<usize> = strategies
+                    .iter()
+                    .map(|s| heat_loss_with(&board, &rules, *s).unwrap())
+                    .collect();
+                assert!(answers.iter().all(|a| *a == answers[0]), "{:?}", answers);
+            }
+        }
+    }
+
+    #[test]
+    fn bad_board() {
+        let err = part1("2413\n32a5\n3255").unwrap_err();
+        let err = err.downcast::<ParseError>().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let err = part2("111").unwrap_err();
+        let err = err.downcast::<UnreachableError>().unwrap();
+        assert_eq!(err.to_string(), "no route leads from 0,0 to 0,2");
+        assert!(part2_route("111").is_err());
+    }
+
+    #[test]
+    fn extreme_runs() {
+        let board = generate_board(13, 13, 7);
+        let strategies = [Strategy::Dijkstra, Strategy::DenseDijkstra];
+
+        // No straight run can be longer than the board, so these are all the same as 13
+        let mut rules = CrucibleRules::new(1, 13);
+        let expected = heat_loss_with(&board, &rules, Strategy::Dijkstra).unwrap();
+        for max_run in [1_000_000_000, usize::MAX] {
+            rules.max_run = max_run;
+            for strategy in strategies {
+                assert_eq!(heat_loss_with(&board, &rules, strategy).unwrap(), expected);
+            }
+        }
+
+        rules.min_run = usize::MAX;
+        assert!(heat_loss_with(&board, &rules, Strategy::DenseDijkstra).is_err());
+
+        assert_eq!(state_count(13, 13, 14), Some(13 * 13 * 4 * 14));
+        assert_eq!(state_count(usize::MAX / 2, 1, 2), None);
+        assert_eq!(state_count(1 << 32, 1 << 32, 1), None);
+    }
 }
@@ -5,12 +5,22 @@ use std::collections::HashSet;
 use std::fmt::Display;
 use std::iter::zip;
 
-use crate::matrix::AoCMatrix;
+use crate::common::{with_lines, NotImplementedError, ParseError};
+use crate::matrix::{AoCMatrix, Direction};
+use crate::parsers::{parse_with, unsigned};
+use crate::solver::{Solver, SolverResult};
+use crate::sparse_grid::{Coord, SparseGrid};
 
 use hex;
-use nom::InputIter;
-
-type BOARD = AoCMatrix<Square>;
+use nom::{
+    bytes::complete::is_not,
+    character::complete::char,
+    combinator::all_consuming,
+    sequence::{preceded, tuple},
+    InputIter,
+};
+
+type BOARD = SparseGrid<Square>;
 type POSITION = (i64, i64);
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -48,44 +58,47 @@ impl std::fmt::Debug for Color {
 }
 
 impl Color {
-    fn from_hex(s: &str) -> Color {
+    fn from_hex(s: &str) -> Option<Color> {
         // Skip first character, starts with #
-        let c1 = &s[1..3];
-        let c2 = &s[3..5];
-        let c3 = &s[5..7];
+        if s.len() != 7 || !s.starts_with('#') {
+            return None;
+        }
+        let c1 = s.get(1..3)?;
+        let c2 = s.get(3..5)?;
+        let c3 = s.get(5..7)?;
 
-        let r = u16::from_str_radix(c1, 16).unwrap();
-        let g = u16::from_str_radix(c2, 16).unwrap();
-        let b = u16::from_str_radix(c3, 16).unwrap();
+        let r = u16::from_str_radix(c1, 16).ok()?;
+        let g = u16::from_str_radix(c2, 16).ok()?;
+        let b = u16::from_str_radix(c3, 16).ok()?;
 
-        return Color { r, g, b };
+        return Some(Color { r, g, b });
     }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 struct Command {
-    direction: char,
+    direction: Direction,
     distance: i64,
     color: Color,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+fn direction_from_char(c: char) -> Option<Direction> {
+    match c {
+        'U' => Some(Direction::North),
+        'D' => Some(Direction::South),
+        'L' => Some(Direction::West),
+        'R' => Some(Direction::East),
+        _ => None,
+    }
 }
-
-impl Direction {
-    fn from_char(c: char) -> Direction {
-        match c {
-            'U' => Direction::Up,
-            'D' => Direction::Down,
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            _ => panic!(),
-        }
+/// Direction from the last digit of a color code
+fn direction_from_digit(c: char) -> Option<Direction> {
+    match c {
+        '0' => Some(Direction::East),
+        '1' => Some(Direction::South),
+        '2' => Some(Direction::West),
+        '3' => Some(Direction::North),
+        _ => None,
     }
 }
 
@@ -112,14 +125,50 @@ struct HorizontalLine {
 /*
  Entry
 */
-pub fn part1(lines: Vec<String>) -> i64 {
-    let cmds: Vec<Command> = lines.iter().map(parse_line).collect();
-    return solve(&cmds);
+const DAY: u32 = 18;
+
+pub fn part1(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let cmds: Vec<Command> = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_line(idx, l))
+        .collect::<Result<_, _>>()?;
+    return Ok(solve(&cmds));
+}
+
+pub fn part2(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part2_lines);
 }
 
-pub fn part2(lines: Vec<String>) -> i64 {
-    let cmds = lines.iter().map(parse_line_color_command).collect();
-    return solve(&cmds);
+pub fn part2_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let cmds = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_line_color_command(idx, l))
+        .collect::<Result<_, _>>()?;
+    return Ok(solve(&cmds));
+}
+
+/*
+    Digging the trench out square by square, to draw it. Its size grows with the plan's
+    distances, so the answers come from the shoelace below instead.
+*/
+
+fn dig_trench(cmds: &[Command]) -> BOARD {
+    let mut pos: Coord = (0, 0);
+    let mut trench = SparseGrid::new();
+    for c in cmds.iter() {
+        let (dr, dc) = c.direction.delta();
+        for _ in 0..c.distance {
+            pos = (pos.0 + dr, pos.1 + dc);
+            trench.insert(pos, Square::Hole(c.color));
+        }
+    }
+    return trench;
 }
 
 /*
@@ -152,11 +201,10 @@ fn pts_from_cmds(cmds: &Vec<Command>) -> (Vec<POSITION>, i64) {
         let d = c.distance;
         l_acc += d;
         cpos = match c.direction {
-            'U' => (cpos.0, cpos.1 + d),
-            'D' => (cpos.0, cpos.1 - d),
-            'L' => (cpos.0 - d, cpos.1),
-            'R' => (cpos.0 + d, cpos.1),
-            _ => panic!(),
+            Direction::North => (cpos.0, cpos.1 + d),
+            Direction::South => (cpos.0, cpos.1 - d),
+            Direction::West => (cpos.0 - d, cpos.1),
+            Direction::East => (cpos.0 + d, cpos.1),
         };
         pvec.push(cpos);
     }
@@ -187,43 +235,92 @@ fn length(a: POSITION, b: POSITION) -> i64 {
  Parsing Input
 */
 
-fn parse_line(l: &String) -> Command {
-    let mut pieces = l.split(' ');
-    let dir = pieces.next().unwrap().chars().next().unwrap();
-    let count: i64 = pieces.next().unwrap().parse().unwrap();
+/// Split a `<direction> <distance> (#<color>)` line into its pieces, with the color still hex
+///     encoded but unwrapped from its parentheses
+fn split_line(idx: usize, l: &str) -> Result<(&str, &str, &str), ParseError> {
+    let (dir, count, color) = parse_with(
+        DAY,
+        idx,
+        l,
+        tuple((
+            is_not(" "),
+            preceded(char(' '), is_not(" ")),
+            preceded(char(' '), is_not(" ")),
+        )),
+        "expected '<direction> <distance> (#<color>)'",
+    )?;
+
+    let color = color
+        .strip_prefix('(')
+        .and_then(|c| c.strip_suffix(')'))
+        .filter(|c| Color::from_hex(c).is_some())
+        .ok_or_else(|| ParseError::at(DAY, idx, l, color, "expected a color like (#70c710)"))?;
+
+    return Ok((dir, count, color));
+}
 
-    let color = Color::from_hex(&pieces.next().unwrap()[1..9]);
+fn parse_line(idx: usize, l: &str) -> Result<Command, ParseError> {
+    let (dir, count, color) = split_line(idx, l)?;
 
-    return Command {
-        direction: dir,
-        distance: count,
-        color,
+    let mut dir_chars = dir.chars();
+    let direction = match (
+        dir_chars.next().and_then(direction_from_char),
+        dir_chars.next(),
+    ) {
+        (Some(d), None) => d,
+        _ => return Err(ParseError::at(DAY, idx, l, dir, "expected U, D, L or R")),
     };
+    let (_, distance) = all_consuming(unsigned::<i64>)(count)
+        .map_err(|_| ParseError::at(DAY, idx, l, count, "invalid distance"))?;
+
+    return Ok(Command {
+        direction,
+        distance,
+        // Already checked when splitting the line
+        color: Color::from_hex(color).unwrap(),
+    });
 }
 
-fn parse_line_color_command(l: &String) -> Command {
-    let mut pieces = l.split(' ');
-    let _dir = pieces.next();
-    let _count = pieces.next();
-
-    let color_str = &pieces.next().unwrap().to_string();
-    let color = Color::from_hex(&color_str[1..9]);
-
-    let dir = match color_str.chars().nth(7).unwrap() {
-        '0' => 'R',
-        '1' => 'D',
-        '2' => 'L',
-        '3' => 'U',
-        _ => panic!("Unrecognized direction"),
-    };
+fn parse_line_color_command(idx: usize, l: &str) -> Result<Command, ParseError> {
+    let (_, _, color_str) = split_line(idx, l)?;
+    let color = Color::from_hex(color_str).unwrap();
+
+    // The color is all hex digits, so these are both safe to slice out
+    let direction = direction_from_digit(color_str.chars().nth(6).unwrap()).ok_or_else(|| {
+        ParseError::at(
+            DAY,
+            idx,
+            l,
+            &color_str[6..],
+            "expected a direction digit of 0-3",
+        )
+    })?;
+
+    let distance = i64::from_str_radix(&color_str[1..6], 16).unwrap();
+
+    return Ok(Command {
+        direction,
+        distance,
+        color,
+    });
+}
 
-    let count = i64::from_str_radix(&color_str[2..7], 16).unwrap();
+pub struct Day18;
 
-    return Command {
-        direction: dir,
-        distance: count,
-        color,
-    };
+impl Solver for Day18 {
+    fn day(&self) -> u32 {
+        18
+    }
+    fn name(&self) -> &'static str {
+        "Lavaduct Lagoon"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 /*
@@ -233,14 +330,13 @@ fn parse_line_color_command(l: &String) -> Command {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let string_input = "R 6 (#70c710)\nD 5 (#0dc571)\nL 2 (#5713f0)\nD 2 (#d2c081)\nR 2 (#59c680)\nD 2 (#411b91)\nL 5 (#8ceee2)\nU 2 (#caa173)\nL 1 (#1b58a2)\nU 2 (#caa171)\nR 2 (#7807d2)\nU 3 (#a77fa3)\nL 2 (#015232)\nU 2 (#7a21e3)".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part1(line_input);
+        let result = part1(line_input).unwrap();
 
         assert_eq!(result, 62);
     }
@@ -248,10 +344,48 @@ mod tests {
     #[test]
     fn part2_test() {
         let string_input = "R 6 (#70c710)\nD 5 (#0dc571)\nL 2 (#5713f0)\nD 2 (#d2c081)\nR 2 (#59c680)\nD 2 (#411b91)\nL 5 (#8ceee2)\nU 2 (#caa173)\nL 1 (#1b58a2)\nU 2 (#caa171)\nR 2 (#7807d2)\nU 3 (#a77fa3)\nL 2 (#015232)\nU 2 (#7a21e3)".to_string();
-        let line_input = string_to_lines(&string_input);
+        let line_input = &string_input;
 
-        let result = part2(line_input);
+        let result = part2(line_input).unwrap();
 
         assert_eq!(result, 952408144115);
     }
+
+    #[test]
+    fn bad_commands() {
+        let err = part1("R 6 (#70c710)\nX 5 (#0dc571)").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        let err = part1("R 6 (#70c7z0)").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+
+        let err = part2("R 6 (#70c717)").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 12));
+    }
+
+    #[test]
+    fn trench_test() {
+        let string_input = "R 6 (#70c710)\nD 5 (#0dc571)\nL 2 (#5713f0)\nD 2 (#d2c081)\nR 2 (#59c680)\nD 2 (#411b91)\nL 5 (#8ceee2)\nU 2 (#caa173)\nL 1 (#1b58a2)\nU 2 (#caa171)\nR 2 (#7807d2)\nU 3 (#a77fa3)\nL 2 (#015232)\nU 2 (#7a21e3)";
+        let cmds: Vec<Command> = string_input
+            .lines()
+            .enumerate()
+            .map(|(idx, l)| parse_line(idx, l))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let trench = dig_trench(&cmds);
+        assert_eq!(
+            trench.to_string(),
+            "#######\n#.....#\n###...#\n..#...#\n..#...#\n###.###\n#...#..\n##..###\n.#....#\n.######"
+        );
+        assert_eq!(dig_trench(&[]).to_string(), "");
+    }
+
+    #[test]
+    fn long_trench() {
+        let result =
+            part1("R 100000 (#000000)\nD 100000 (#000000)\nL 100000 (#000000)\nU 100000 (#000000)");
+
+        assert_eq!(result.unwrap(), 10000200001);
+    }
 }
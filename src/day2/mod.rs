@@ -1,30 +1,67 @@
+use std::error::Error;
+use std::io::BufRead;
+
+use crate::common::{try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::parsers::unsigned;
+use crate::solver::{Solver, SolverResult};
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, digit1, space0, space1},
-    sequence::tuple,
+    character::complete::{alpha1, space0, space1},
+    sequence::{delimited, tuple},
     IResult,
 };
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let games: Vec<Game> = lines.iter().map(|l| parse_game(l)).collect();
+const DAY: u32 = 2;
 
-    let max_round = Round {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
+const MAX_ROUND: Round = Round {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
+pub fn part1(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part1_lines);
+}
 
-    let count_games = games.iter().filter(|g| !g.exceeds_round(&max_round));
+pub fn part1_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let games = parse_games(lines)?;
 
-    return count_games.map(|g| g.id).sum();
+    return Ok(games.iter().map(possible_id).sum());
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let games = lines.iter().map(|l| parse_game(l));
+pub fn part2(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let games = parse_games(lines)?;
 
-    let powers = games.map(|g| g.min_round()).map(|r| r.power());
+    let powers = games.iter().map(|g| g.min_round()).map(|r| r.power());
 
-    return powers.sum();
+    return Ok(powers.sum());
+}
+
+/// `part1`, reading a game at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        Ok(acc + possible_id(&parse_game(idx, l)?))
+    });
+}
+
+/// `part2`, reading a game at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        Ok(acc + parse_game(idx, l)?.min_round().power())
+    });
+}
+
+/// The game's id if it could have been played with `MAX_ROUND`'s cubes, otherwise 0
+fn possible_id(game: &Game) -> u32 {
+    return if game.exceeds_round(&MAX_ROUND) {
+        0
+    } else {
+        game.id
+    };
 }
 
 #[derive(Clone)]
@@ -79,63 +116,96 @@ impl Game {
     }
 }
 
-fn parse_game(l: &str) -> Game {
-    let game_result = parse_game_prefix(l);
-
-    match game_result {
-        Ok((rest, id)) => {
-            let rounds = parse_rounds(rest);
-            return Game { id, rounds };
-        }
-        Err(_) => panic!("Unable to parse game id"),
-    };
+fn parse_games(lines: &[&str]) -> Result<Vec<Game>, ParseError> {
+    return lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_game(idx, l))
+        .collect();
 }
 
-fn parse_game_prefix(l: &str) -> IResult<&str, u32> {
-    let (rest, (_, id, _)) = tuple((tag("Game "), digit1, tag(": ")))(l)?;
+fn parse_game(idx: usize, l: &str) -> Result<Game, ParseError> {
+    let (rest, id) = parse_game_prefix(l)
+        .map_err(|e| ParseError::from_nom(DAY, idx, l, e, "expected 'Game <id>: '"))?;
 
-    return Ok((rest, id.parse().unwrap()));
-}
+    let rounds = rest
+        .split(';')
+        .map(|r| parse_round(idx, l, r))
+        .collect::<Result<Vec<Round>, ParseError>>()?;
 
-fn parse_rounds(l: &str) -> Vec<Round> {
-    return l.split(';').map(parse_round).collect();
+    return Ok(Game { id, rounds });
 }
 
-fn parse_round(l: &str) -> Round {
-    let color_counts = l.split(", ").map(parse_color_count);
+fn parse_game_prefix(l: &str) -> IResult<&str, u32> {
+    return delimited(tag("Game "), unsigned, tag(": "))(l);
+}
 
+fn parse_round(idx: usize, line: &str, l: &str) -> Result<Round, ParseError> {
     let mut round = Round {
         red: 0,
         green: 0,
         blue: 0,
     };
-    for c in color_counts.into_iter() {
-        match c {
-            Ok((_, ("red", ct))) => round.red = ct,
-            Ok((_, ("green", ct))) => round.green = ct,
-            Ok((_, ("blue", ct))) => round.blue = ct,
-            _ => panic!("Parsed an unknown color"),
+    for c in l.split(", ") {
+        let (ct, color) = parse_color_count(c)
+            .map_err(|e| ParseError::from_nom(DAY, idx, line, e, "expected '<count> <color>'"))?
+            .1;
+        match color {
+            "red" => round.red = ct,
+            "green" => round.green = ct,
+            "blue" => round.blue = ct,
+            _ => {
+                let msg = format!("unknown color '{}'", color);
+                return Err(ParseError::at(DAY, idx, line, color, msg));
+            }
         }
     }
 
-    return round;
+    return Ok(round);
 }
 
-fn parse_color_count(l: &str) -> IResult<&str, (&str, u32)> {
-    let (rest, (_, ct, _, color)) = tuple((space0, digit1, space1, alpha1))(l)?;
-    return Ok((rest, (color, ct.parse().unwrap())));
+fn parse_color_count(l: &str) -> IResult<&str, (u32, &str)> {
+    let (rest, (_, ct, _, color)) = tuple((space0, unsigned, space1, alpha1))(l)?;
+    return Ok((rest, (ct, color)));
+}
+
+pub struct Day2;
+
+impl Solver for Day2 {
+    fn day(&self) -> u32 {
+        2
+    }
+    fn name(&self) -> &'static str {
+        "Cube Conundrum"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part_1() -> () {
         let test_input: String = String::from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 8);
     }
@@ -143,9 +213,28 @@ mod tests {
     #[test]
     fn part_2() -> () {
         let test_input: String = String::from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 2286);
     }
+
+    #[test]
+    fn bad_color() {
+        let test_lines = "Game 1: 3 blue, 4 red\nGame 2: 1 blue; 2 purple";
+        let err = part1(test_lines).unwrap_err();
+
+        assert_eq!((err.line, err.column), (2, 19));
+    }
+
+    #[test]
+    fn streaming() {
+        let test_input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\nGame 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\nGame 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\nGame 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green\n";
+
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 8);
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 2286);
+        assert!(
+            part1_stream("Game 1: 3 blue, 4 red\nGame 2: 1 blue; 2 purple".as_bytes()).is_err()
+        );
+    }
 }
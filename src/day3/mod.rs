@@ -2,13 +2,14 @@ use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
 
 use crate::common::*;
+use crate::parsers::{parse_with, unsigned};
+use crate::solver::{Solver, SolverResult};
 
 use nom::{
     branch::alt,
     bytes::complete::{take, take_till1},
     character::complete::digit1,
     multi::many1,
-    IResult,
 };
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Copy)]
@@ -31,7 +32,7 @@ impl Number {
         };
     }
     fn overlap(&self, id: &Identifier) -> bool {
-        return (max((id.col - 1) as i32, self.start_col as i32)
+        return (max(id.col as i32 - 1, self.start_col as i32)
             <= min((id.col + 1).try_into().unwrap(), self.end_col as i32))
             && ((id.line as i32 - self.line as i32).abs() <= 1);
     }
@@ -64,13 +65,23 @@ impl NumberIDPair {
     }
 }
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let results = lines_to_number_id_pairs(lines);
-    return results.iter().map(|n| n.number.value).sum();
+const DAY: u32 = 3;
+
+pub fn part1(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let results = lines_to_number_id_pairs(lines)?;
+    return Ok(results.iter().map(|n| n.number.value).sum());
+}
+
+pub fn part2(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part2_lines);
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let results = lines_to_number_id_pairs(lines);
+pub fn part2_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let results = lines_to_number_id_pairs(lines)?;
 
     let mut id_map: HashMap<Identifier, Vec<Number>> = HashMap::new();
 
@@ -91,7 +102,7 @@ pub fn part2(lines: Vec<String>) -> u32 {
     }
 
     // Find all IDs from the map (implicity * IDs) which touch two values, and compute the result
-    return id_map
+    return Ok(id_map
         .iter()
         .map(|(_, v)| {
             if v.len() == 2 {
@@ -100,35 +111,23 @@ pub fn part2(lines: Vec<String>) -> u32 {
                 0
             }
         })
-        .sum();
+        .sum());
 }
 
-fn lines_to_number_id_pairs(lines: Vec<String>) -> Vec<NumberIDPair> {
-    let (numbers, mut ids): (Vec<_>, Vec<_>) = lines
+fn lines_to_number_id_pairs(lines: &[&str]) -> Result<Vec<NumberIDPair>, ParseError> {
+    let (numbers, ids): (Vec<_>, Vec<_>) = lines
         .iter()
         .enumerate()
-        .map(|(idx, l)| {
-            dbg!(&l);
-            let result = parse_line(idx, l);
-            return match result {
-                Ok((_, results)) => results,
-                Err(e) => panic!("Encountered error parsing line {}: {:?}", l, e),
-            };
-        })
+        .map(|(idx, l)| parse_line(idx, l))
+        .collect::<Result<Vec<_>, ParseError>>()?
+        .into_iter()
         .unzip();
 
-    let ids = ids
-        .iter_mut()
-        .reduce(|a, b| {
-            a.append(b);
-            a
-        })
-        .unwrap()
-        .to_vec();
+    let ids = ids.into_iter().flatten().collect();
 
     let results = populate_number_id_pairs(numbers, ids);
 
-    return results;
+    return Ok(results);
 }
 
 fn populate_number_id_pairs(
@@ -157,7 +156,7 @@ fn populate_number_id_pairs(
     return results;
 }
 
-fn parse_line(l_idx: usize, l: &str) -> IResult<&str, (HashSet<Number>, Vec<Identifier>)> {
+fn parse_line(l_idx: usize, l: &str) -> Result<(HashSet<Number>, Vec<Identifier>), ParseError> {
     // TODO: rewrite this with nom_locate
 
     // many1 applies the internal alt parser until the line terminates
@@ -166,8 +165,13 @@ fn parse_line(l_idx: usize, l: &str) -> IResult<&str, (HashSet<Number>, Vec<Iden
     //      digit1 matches as many digits as it can, with a minimum of 1
     //      take(1usize) takes a single byte. This is a standing for anychar, since we want a &str, not a char
     // This parser parses the line as &strs into one of 3 string classes: "...", "1234", "*"
-    let (rem, parsed): (&str, Vec<&str>) =
-        many1(alt((take_till1(|c| c != '.'), digit1, take(1usize))))(l)?;
+    let parsed: Vec<&str> = parse_with(
+        DAY,
+        l_idx,
+        l,
+        many1(alt((take_till1(|c| c != '.'), digit1, take(1usize)))),
+        "expected a row of the schematic",
+    )?;
 
     let mut numbers: HashSet<Number> = HashSet::new();
     let mut ids: Vec<Identifier> = vec![];
@@ -181,7 +185,9 @@ fn parse_line(l_idx: usize, l: &str) -> IResult<&str, (HashSet<Number>, Vec<Iden
          Default case, we parsed a number
          Otherwise, if we parsed a single character, and distinguish the character from a period
         */
-        if let Ok(n) = substr.parse::<u32>() {
+        if substr.starts_with(|c: char| c.is_ascii_digit()) {
+            let (_, n) = unsigned::<u32>(substr)
+                .map_err(|_| ParseError::at(DAY, l_idx, l, substr, "part number out of range"))?;
             let new_number = Number::new(n, l_idx, col, col + substr.len() - 1);
             numbers.insert(new_number);
         } else if slen == 1 {
@@ -199,19 +205,36 @@ fn parse_line(l_idx: usize, l: &str) -> IResult<&str, (HashSet<Number>, Vec<Iden
         col += slen
     }
 
-    return Ok((rem, (numbers, ids)));
+    return Ok((numbers, ids));
+}
+
+pub struct Day3;
+
+impl Solver for Day3 {
+    fn day(&self) -> u32 {
+        3
+    }
+    fn name(&self) -> &'static str {
+        "Gear Ratios"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() -> () {
         let test_input: String = String::from("467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 4361);
     }
@@ -219,8 +242,8 @@ mod tests {
     #[test]
     fn part2_test() -> () {
         let test_input: String = String::from("467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..");
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 467835);
     }
@@ -1,77 +1,160 @@
 use std::collections::hash_set::HashSet;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::BufRead;
 
-use crate::common::intersect_sets;
+use crate::common::{intersect_sets, try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::parsers::unsigned_list;
+use crate::solver::{Solver, SolverResult};
 
-use itertools::Itertools;
+use nom::combinator::all_consuming;
 
-pub fn part1(lines: Vec<String>) -> usize {
-    let scores = get_points_cards(lines);
-    let result = scores
-        .iter()
-        .map(|l| {
-            if l > &0 {
-                (2_usize).pow(*l as u32 - 1 as u32)
-            } else {
-                0
-            }
-        })
-        .sum();
+const DAY: u32 = 4;
 
-    return result;
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part1_lines);
 }
 
-pub fn part2(lines: Vec<String>) -> usize {
-    let scores = get_points_cards(lines);
-    let mut card_counts = vec![0; scores.len()];
-
-    for (i, s) in scores.iter().enumerate() {
-        // Apparently we count cards even if they did lose, previously I had
-        //  a piece of logic that checked whether the score was positive to decide
-        //  whether we increment extras
-        card_counts[i] += 1;
-        for n in 1..s + 1 {
-            card_counts[i + n] += card_counts[i];
-        }
+pub fn part1_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let scores = get_points_cards(lines)?;
+    return Ok(scores.into_iter().map(points).sum());
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<usize, ParseError> {
+    let scores = get_points_cards(lines)?;
+    let mut counter = CardCounter::default();
+    for s in scores {
+        counter.add(s);
     }
 
-    return card_counts.iter().sum();
+    return Ok(counter.total);
+}
+
+/// `part1`, reading a card at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<usize, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        Ok(acc + points(card_matches(idx, l)?))
+    });
+}
+
+/// `part2`, reading a card at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<usize, Box<dyn Error>> {
+    let counter = try_fold_lines(reader, CardCounter::default(), |mut counter, idx, l| {
+        counter.add(card_matches(idx, l)?);
+        Ok(counter)
+    })?;
+    return Ok(counter.total);
 }
 
-fn get_points_cards(lines: Vec<String>) -> Vec<usize> {
-    let result: Vec<usize> = lines
+fn points(matches: usize) -> usize {
+    if matches > 0 {
+        return 2_usize.pow(matches as u32 - 1);
+    }
+    return 0;
+}
+
+/// Counts the cards won, copies included, one card at a time. Only copies of the cards still to
+///     come are kept, so this never holds more than the most numbers any card matches.
+#[derive(Default)]
+struct CardCounter {
+    /// Copies won of each of the next few cards
+    pending: VecDeque<usize>,
+    total: usize,
+}
+
+impl CardCounter {
+    fn add(&mut self, matches: usize) {
+        // Every card counts, even ones which win nothing
+        let copies = 1 + self.pending.pop_front().unwrap_or(0);
+        self.total += copies;
+
+        for n in 0..matches {
+            match self.pending.get_mut(n) {
+                Some(pending) => *pending += copies,
+                None => self.pending.push_back(copies),
+            }
+        }
+    }
+}
+
+fn get_points_cards(lines: &[&str]) -> Result<Vec<usize>, ParseError> {
+    return lines
         .iter()
-        .map(|l| -> (HashSet<u32>, HashSet<u32>) {
-            let split1: &str = l.split(':').collect::<Vec<&str>>()[1];
-            let split2: Vec<&str> = split1.split('|').collect::<Vec<&str>>();
-            let parsed_data: Vec<Vec<u32>> = split2.iter().map(parse_space_delimited).collect();
-            (
-                HashSet::from_iter(parsed_data[0].clone()),
-                HashSet::from_iter(parsed_data[1].clone()),
-            )
-        })
-        .map(|(a, b)| intersect_sets(a, b).into_iter().try_len().unwrap())
+        .enumerate()
+        .map(|(idx, l)| card_matches(idx, l))
         .collect();
+}
+
+/// How many of the numbers we have are winning numbers
+fn card_matches(idx: usize, l: &str) -> Result<usize, ParseError> {
+    let (winning, have) = parse_card(idx, l)?;
+    return Ok(intersect_sets(winning, have).len());
+}
+
+fn parse_card(idx: usize, l: &str) -> Result<(HashSet<u32>, HashSet<u32>), ParseError> {
+    let (_, numbers) = l
+        .split_once(':')
+        .ok_or_else(|| ParseError::new(DAY, idx, 0, l, "expected 'Card <id>:'"))?;
+    let (winning, have) = numbers
+        .split_once('|')
+        .ok_or_else(|| ParseError::at(DAY, idx, l, numbers, "expected '|' between numbers"))?;
+
+    return Ok((
+        HashSet::from_iter(parse_numbers(idx, l, winning)?),
+        HashSet::from_iter(parse_numbers(idx, l, have)?),
+    ));
+}
+
+/// The numbers in `v`, one side of the card's `|`, which is a slice of `line`
+fn parse_numbers(idx: usize, line: &str, v: &str) -> Result<Vec<u32>, ParseError> {
+    let (_, nums) = all_consuming(unsigned_list)(v).map_err(|e| {
+        ParseError::from_nom(DAY, idx, line, e, "expected numbers separated by spaces")
+    })?;
 
-    return result;
+    return Ok(nums);
 }
 
-fn parse_space_delimited(v: &&str) -> Vec<u32> {
-    let digits: Vec<&str> = v.split(' ').filter(|s| s.len() > 0).collect();
-    let nums: Vec<u32> = digits.iter().map(|s| s.parse().unwrap()).collect();
+pub struct Day4;
 
-    return nums;
+impl Solver for Day4 {
+    fn day(&self) -> u32 {
+        4
+    }
+    fn name(&self) -> &'static str {
+        "Scratchcards"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() -> () {
         let test_input: String = String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\nCard 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\nCard 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1\nCard 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\nCard 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\nCard 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 13);
     }
@@ -79,9 +162,26 @@ mod tests {
     #[test]
     fn part2_test() -> () {
         let test_input: String = String::from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\nCard 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\nCard 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1\nCard 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\nCard 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\nCard 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11");
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 30);
     }
+
+    #[test]
+    fn streaming() {
+        let test_input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\nCard 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\nCard 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1\nCard 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\nCard 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\nCard 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11\n";
+
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 13);
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 30);
+    }
+
+    #[test]
+    fn bad_cards() {
+        let err = part1("Card 1: 41 48 | 83 86\nCard 2: 13 3x | 61 30").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 13));
+
+        let err = part1("Card 1: 41 48 |").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 16));
+    }
 }
@@ -3,259 +3,357 @@
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, digit1},
-    multi::many1,
-    sequence::{preceded, separated_pair, tuple},
+    character::complete::{alpha1, space1},
+    sequence::{preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
-use crate::half_interval::HalfInterval;
+use num::PrimInt;
+use std::error::Error;
+
+use crate::common::{blocks, input_lines, unsplit, NoRouteError, NotImplementedError, ParseError};
+use crate::half_interval::{HalfInterval, IntervalSet};
+use crate::parsers::{header, parse_with, unsigned, unsigned_list};
+use crate::search::bfs_to;
+use crate::shift_map::ShiftMap;
+use crate::solver::{Solver, SolverResult};
+
+/// Values in the almanac, which can be well past u32::MAX
+type VALUE = u64;
 
 #[derive(Debug, Clone, Copy)]
-struct SeedRange {
-    dest: HalfInterval,
-    source: HalfInterval,
+struct SeedRange<T: PrimInt> {
+    dest: HalfInterval<T>,
+    source: HalfInterval<T>,
 }
 
-impl SeedRange {
-    fn new(dest: u32, source: u32, range: u32) -> SeedRange {
-        return SeedRange {
-            dest: HalfInterval::new(dest, range),
-            source: HalfInterval::new(source, range),
-        };
+impl<T: PrimInt> SeedRange<T> {
+    /// None if either interval runs past the largest T
+    fn new(dest: T, source: T, range: T) -> Option<SeedRange<T>> {
+        return Some(SeedRange {
+            dest: HalfInterval::checked_new(dest, range)?,
+            source: HalfInterval::checked_new(source, range)?,
+        });
     }
-    fn destination(
-        &self,
-        int: HalfInterval,
-    ) -> (
-        Option<HalfInterval>,
-        Option<HalfInterval>,
-        Option<HalfInterval>,
-    ) {
-        let center = self.source.intersect(&int);
-        let (left, right) = int.diff(&self.source);
-
-        return (left, self.shift_interval_by_delta(center), right);
+}
+
+/// One of the almanac's maps, from one category to another
+#[derive(Debug, Clone)]
+struct SeedMap<T: PrimInt> {
+    from: String,
+    to: String,
+    map: ShiftMap<T>,
+}
+
+impl<T: PrimInt> SeedMap<T> {
+    /// None if the ranges' sources overlap
+    fn new(from: String, to: String, ranges: &[SeedRange<T>]) -> Option<SeedMap<T>> {
+        let map = ShiftMap::from_pieces(ranges.iter().map(|r| (r.source, r.dest.lb())))?;
+        return Some(SeedMap { from, to, map });
     }
-    fn shift_interval_by_delta(&self, int: Option<HalfInterval>) -> Option<HalfInterval> {
-        return match int {
-            Some(int) => {
-                let shift = self.dest.lb() as i64 - self.source.lb() as i64;
-                Some(HalfInterval::new(
-                    (int.lb() as i64 + shift) as u32,
-                    int.delta(),
-                ))
-            }
-            None => None,
+    /// The single map doing `self` then `next`, from this map's category to `next`'s
+    fn then(&self, next: &SeedMap<T>) -> SeedMap<T> {
+        return SeedMap {
+            from: self.from.clone(),
+            to: next.to.clone(),
+            map: self.map.then(&next.map),
         };
     }
+    /// The same map followed backwards, None if it sends more than one value to the same place
+    fn inverse(&self) -> Option<SeedMap<T>> {
+        return Some(SeedMap {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            map: self.map.inverse()?,
+        });
+    }
+    /// Where every value in `src` ends up. Values outside every source range map to themselves.
+    fn map_set(&self, src: &IntervalSet<T>) -> IntervalSet<T> {
+        return self.map.map_set(src);
+    }
 }
 
+/// A map followed forwards, or backwards to find every value which ends up in a set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Forward(usize),
+    Backward(usize),
+}
+
+/// The almanac's maps, found by the categories they join rather than their order in the file
 #[derive(Debug, Clone)]
-struct SeedMap {
-    from: String,
-    to: String,
-    ranges: Vec<SeedRange>,
+pub struct Almanac {
+    maps: Vec<SeedMap<VALUE>>,
 }
 
-impl SeedMap {
-    fn new(from: String, to: String, ranges: Vec<SeedRange>) -> SeedMap {
-        let ranges = ranges.to_owned();
-        return SeedMap { from, to, ranges };
+impl Almanac {
+    /// Parse a whole almanac, seeds line included
+    pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+        let (_, almanac) = parse_almanac(input, parse_seeds_individual)?;
+        return Ok(almanac);
     }
-    fn find_destinations(&self, src: HalfInterval) -> Vec<HalfInterval> {
-        let mut intervals_to_iterate = vec![src];
-        let mut finished_intervals: Vec<HalfInterval> = vec![];
 
-        // dbg!(&self.ranges);
+    /// Every category joined by a map, in the order they first appear
+    pub fn categories(&self) -> Vec<&str> {
+        return self
+            .maps
+            .iter()
+            .flat_map(|m| [m.from.as_str(), m.to.as_str()])
+            .unique()
+            .collect();
+    }
 
-        for range in self.ranges.iter() {
-            // dbg!(&range);
-            let local_intervals_to_iterate = intervals_to_iterate.clone();
-            intervals_to_iterate.clear();
+    /// Where the `from` values end up as `to` values. Maps can be followed backwards, so this
+    ///     works in either direction, though going backwards a value can come from many others.
+    pub fn convert(
+        &self,
+        from: &str,
+        to: &str,
+        values: &IntervalSet<VALUE>,
+    ) -> Result<IntervalSet<VALUE>, NoRouteError> {
+        if let Some(composed) = self.compose(from, to)? {
+            return Ok(composed.map_set(values));
+        }
 
-            for int in local_intervals_to_iterate.iter() {
-                // dbg!(&int);
-                let (left, center, right) = range.destination(*int);
+        // Some map on the way back merges values, so each step has to be undone in turn
+        let steps = self.route(from, to)?;
+        return Ok(steps
+            .into_iter()
+            .fold(values.clone(), |current, step| match step {
+                Step::Forward(i) => self.maps[i].map_set(&current),
+                Step::Backward(i) => self.maps[i].map.preimage(&current),
+            }));
+    }
 
-                // dbg!(&left, &center, &right);
-                if let Some(left) = left {
-                    // We sorted by the source ranges, so anything to the left is done
-                    finished_intervals.push(left);
-                };
-                if let Some(center) = center {
-                    // Anything that overlapped is sent to its destination
-                    finished_intervals.push(center);
-                };
-                if let Some(right) = right {
-                    // Anything else needs to be processed on coming mappings
-                    intervals_to_iterate.push(right);
+    /// Every map on the way from `from` to `to` folded into one, so values only go through a
+    ///     single map. None if the way goes backwards through a map which merges values, which
+    ///     no single map can undo.
+    fn compose(&self, from: &str, to: &str) -> Result<Option<SeedMap<VALUE>>, NoRouteError> {
+        let identity = SeedMap {
+            from: from.to_string(),
+            to: from.to_string(),
+            map: ShiftMap::identity(),
+        };
+        return Ok(self
+            .route(from, to)?
+            .into_iter()
+            .try_fold(identity, |composed, step| {
+                let next = match step {
+                    Step::Forward(i) => self.maps[i].clone(),
+                    Step::Backward(i) => self.maps[i].inverse()?,
                 };
-                // dbg!(&intervals_to_iterate);
-            }
-        }
+                Some(composed.then(&next))
+            }));
+    }
 
-        // At the end, anything that hasn't been mapped maps to itself
-        finished_intervals.append(&mut intervals_to_iterate);
+    /// The fewest maps leading from `from` to `to`
+    fn route(&self, from: &str, to: &str) -> Result<Vec<Step>, NoRouteError> {
+        let neighbours = |category: &String| {
+            self.maps
+                .iter()
+                .filter_map(|m| {
+                    if m.from == *category {
+                        Some(m.to.clone())
+                    } else if m.to == *category {
+                        Some(m.from.clone())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let search = bfs_to([from.to_string()], neighbours, |category| category == to);
+
+        let categories = search.goal_path().ok_or_else(|| NoRouteError {
+            from: from.to_string(),
+            to: to.to_string(),
+        })?;
+        return Ok(categories
+            .windows(2)
+            .map(|pair| self.step_between(&pair[0], &pair[1]))
+            .collect());
+    }
 
-        return finished_intervals;
+    /// The map from `from` to `to`, or failing that one back from `to` to `from`
+    fn step_between(&self, from: &str, to: &str) -> Step {
+        let forward = self.maps.iter().position(|m| m.from == from && m.to == to);
+        return match forward {
+            Some(i) => Step::Forward(i),
+            None => {
+                let backward = self.maps.iter().position(|m| m.from == to && m.to == from);
+                Step::Backward(backward.expect("route only follows existing maps"))
+            }
+        };
     }
 }
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let lines: Vec<&String> = lines.iter().filter(|l| !l.is_empty()).collect();
-    let mut line_iter = lines.iter();
-
-    let l = line_iter.next().unwrap();
-    let (_, seeds) = parse_seeds_individual(l).unwrap();
+const DAY: u32 = 5;
 
-    let mut maps = vec![];
+pub fn part1(input: &str) -> Result<VALUE, Box<dyn Error>> {
+    let (seeds, almanac) = parse_almanac(input, parse_seeds_individual)?;
 
-    while let Some(l) = line_iter.next() {
-        let (_, mut seed_map) = parse_map_title(l).unwrap();
-        let ranges = &mut seed_map.ranges;
+    return Ok(lowest_location(seeds, &almanac)?.ok_or_else(|| no_seeds_error(input))?);
+}
 
-        while let Some(l) = line_iter
-            .peeking_take_while(|l| l.chars().next().unwrap().is_digit(10))
-            .next()
-        {
-            let (_, r) = parse_range(l).unwrap();
-            ranges.push(r);
-        }
+pub fn part2(input: &str) -> Result<VALUE, Box<dyn Error>> {
+    let (seeds, almanac) = parse_almanac(input, parse_seeds_intervals)?;
 
-        ranges.sort_by(|r1, r2| r1.source.lb().cmp(&r2.source.lb()));
+    return Ok(lowest_location(seeds, &almanac)?.ok_or_else(|| no_seeds_error(input))?);
+}
 
-        maps.push(seed_map);
-    }
+/// Every seed goes through the maps together, composed into one from seed to location
+fn lowest_location(
+    seeds: Vec<HalfInterval<VALUE>>,
+    almanac: &Almanac,
+) -> Result<Option<VALUE>, NoRouteError> {
+    let seeds: IntervalSet<VALUE> = seeds.into_iter().collect();
+    let locations = almanac.convert("seed", "location", &seeds)?;
 
-    let dests = seeds.iter().map(|s| {
-        // dbg!(&s);
-        let mut current_intervals = vec![*s];
-        let mut next_loop_intervals = vec![];
-        for m in maps.iter() {
-            for int in current_intervals.iter() {
-                let mut new_ints = m.find_destinations(*int);
-                next_loop_intervals.append(&mut new_ints);
-            }
-            current_intervals = next_loop_intervals.clone();
-            next_loop_intervals.clear();
-        }
-        current_intervals.iter().map(|int| int.lb()).min().unwrap()
-    });
+    return Ok(locations.min());
+}
 
-    return dests.min().unwrap();
+fn no_seeds_error(input: &str) -> ParseError {
+    let seeds_line = input_lines(input).next().unwrap_or("");
+    return ParseError::new(DAY, 0, 0, seeds_line, "every seed range is empty");
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let lines: Vec<&String> = lines.iter().filter(|l| !l.is_empty()).collect();
-    let mut line_iter = lines.iter();
+/// Parse the seeds line with `parse_seeds`, followed by each of the maps. Line numbers are those
+///     of the original input, blank lines included.
+fn parse_almanac(
+    input: &str,
+    parse_seeds: fn(usize, &str) -> Result<Vec<HalfInterval<VALUE>>, ParseError>,
+) -> Result<(Vec<HalfInterval<VALUE>>, Almanac), ParseError> {
+    // Blocks are never empty, so always have a first line
+    let mut block_iter = blocks(input).map(|(idx, block)| (idx, input_lines(block)));
 
-    let l = line_iter.next().unwrap();
-    let (_, seeds) = parse_seeds_intervals(l).unwrap();
+    let seeds = match block_iter.next() {
+        Some((idx, mut block)) => parse_seeds(idx, block.next().unwrap())?,
+        None => return Err(ParseError::new(DAY, 0, 0, "", "missing seeds")),
+    };
 
     let mut maps = vec![];
 
-    while let Some(l) = line_iter.next() {
-        let (_, mut seed_map) = parse_map_title(l).unwrap();
-        let ranges = &mut seed_map.ranges;
-
-        while let Some(l) = line_iter
-            .peeking_take_while(|l| l.chars().next().unwrap().is_digit(10))
-            .next()
-        {
-            let (_, r) = parse_range(l).unwrap();
-            ranges.push(r);
-        }
-
-        ranges.sort_by(|r1, r2| r1.source.lb().cmp(&r2.source.lb()));
-
+    for (title_idx, mut block) in block_iter {
+        let title = block.next().unwrap();
+        let (from, to) = parse_with(
+            DAY,
+            title_idx,
+            title,
+            parse_map_title,
+            "expected '<from>-to-<to> map:'",
+        )?;
+        let ranges = block
+            .enumerate()
+            .map(|(i, l)| parse_range(title_idx + 1 + i, l))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let seed_map = SeedMap::new(from.to_owned(), to.to_owned(), &ranges).ok_or_else(|| {
+            ParseError::new(DAY, title_idx, 0, title, "the map's source ranges overlap")
+        })?;
         maps.push(seed_map);
     }
 
-    let dests = seeds.iter().map(|s| {
-        // dbg!(&s);
-        let mut current_intervals = vec![*s];
-        let mut next_loop_intervals = vec![];
-        for m in maps.iter() {
-            for int in current_intervals.iter() {
-                let mut new_ints = m.find_destinations(*int);
-                next_loop_intervals.append(&mut new_ints);
-            }
-            current_intervals = next_loop_intervals.clone();
-            next_loop_intervals.clear();
-        }
-        current_intervals.iter().map(|int| int.lb()).min().unwrap()
-    });
-
-    return dests.min().unwrap();
+    return Ok((seeds, Almanac { maps }));
 }
 
-fn parse_seeds_individual(l: &str) -> IResult<&str, Vec<HalfInterval>> {
-    let (rem, digits) = preceded(tag("seeds:"), many1(space_separated))(l)?;
+fn parse_seeds_individual(idx: usize, l: &str) -> Result<Vec<HalfInterval<VALUE>>, ParseError> {
+    let digits = parse_seed_numbers(idx, l)?;
 
-    let parsed_digits = digits
+    return Ok(digits
         .into_iter()
-        .map(|e| e.parse().unwrap())
         .map(|v| HalfInterval::new(v, 1))
-        .collect();
-
-    return Ok((rem, parsed_digits));
+        .collect());
 }
 
-fn parse_seeds_intervals(l: &str) -> IResult<&str, Vec<HalfInterval>> {
-    let (rem, digits) = preceded(tag("seeds:"), many1(space_separated))(l)?;
+fn parse_seeds_intervals(idx: usize, l: &str) -> Result<Vec<HalfInterval<VALUE>>, ParseError> {
+    let digits = parse_seed_numbers(idx, l)?;
+    if digits.len() % 2 != 0 {
+        return Err(ParseError::new(
+            DAY,
+            idx,
+            l.len(),
+            l,
+            "seed ranges must come in start/length pairs",
+        ));
+    }
 
-    let parsed_digits = digits
+    return digits
         .chunks(2)
-        .into_iter()
         .map(|c| {
-            (
-                c[0].parse::<u32>().unwrap().to_owned(),
-                c[1].parse::<u32>().unwrap().to_owned(),
-            )
+            HalfInterval::checked_new(c[0], c[1]).ok_or_else(|| {
+                ParseError::new(DAY, idx, 0, l, "seed range runs past the largest value")
+            })
         })
-        .map(|(a, b)| HalfInterval::new(a, b))
         .collect();
-
-    return Ok((rem, parsed_digits));
 }
 
-fn parse_map_title(l: &str) -> IResult<&str, SeedMap> {
-    let (rem, (from, to)) = separated_pair(alpha1, tag("-to-"), alpha1)(l)?;
+fn parse_seed_numbers(idx: usize, l: &str) -> Result<Vec<VALUE>, ParseError> {
+    return parse_with(
+        DAY,
+        idx,
+        l,
+        preceded(header("seeds"), unsigned_list),
+        "expected 'seeds: <numbers>'",
+    );
+}
 
-    let ranges = vec![];
-    return Ok((rem, SeedMap::new(from.to_owned(), to.to_owned(), ranges)));
+fn parse_map_title(l: &str) -> IResult<&str, (&str, &str)> {
+    return terminated(separated_pair(alpha1, tag("-to-"), alpha1), tag(" map:"))(l);
 }
 
-fn parse_range(l: &str) -> IResult<&str, SeedRange> {
-    let (rem, (dest, src, range)) = tuple((digit1, space_separated, space_separated))(l)?;
-
-    return Ok((
-        rem,
-        SeedRange::new(
-            dest.parse().unwrap(),
-            src.parse().unwrap(),
-            range.parse().unwrap(),
-        ),
-    ));
+fn parse_range(idx: usize, l: &str) -> Result<SeedRange<VALUE>, ParseError> {
+    let (dest, src, range) = parse_with(
+        DAY,
+        idx,
+        l,
+        tuple((
+            unsigned,
+            preceded(space1, unsigned),
+            preceded(space1, unsigned),
+        )),
+        "expected '<dest> <source> <length>'",
+    )?;
+
+    return SeedRange::new(dest, src, range)
+        .ok_or_else(|| ParseError::new(DAY, idx, 0, l, "range runs past the largest value"));
 }
 
-fn space_separated(l: &str) -> IResult<&str, &str> {
-    return preceded(tag(" "), digit1)(l);
+pub struct Day5;
+
+impl Solver for Day5 {
+    fn day(&self) -> u32 {
+        5
+    }
+    fn name(&self) -> &'static str {
+        "If You Give A Seed A Fertilizer"
+    }
+    /// The almanac is split into blocks, with their line numbers, while solving
+    fn split<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        return vec![input];
+    }
+    fn solve_split(&self, pieces: &[&str], part: u32) -> SolverResult {
+        let input = unsplit(pieces);
+        return match part {
+            1 => Ok(part1(&input)?.into()),
+            2 => Ok(part2(&input)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4";
 
     #[test]
     fn part1_test() -> () {
         let test_input: String = String::from(
             "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4",
         );
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 35);
     }
@@ -265,9 +363,150 @@ mod tests {
         let test_input: String = String::from(
             "seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-fertilizer map:\n0 15 37\n37 52 2\n39 0 15\n\nfertilizer-to-water map:\n49 53 8\n0 11 42\n42 0 7\n57 7 4\n\nwater-to-light map:\n88 18 7\n18 25 70\n\nlight-to-temperature map:\n45 77 23\n81 45 19\n68 64 13\n\ntemperature-to-humidity map:\n0 69 1\n1 0 69\n\nhumidity-to-location map:\n60 56 37\n56 93 4",
         );
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 46);
     }
+
+    #[test]
+    fn wide_values() {
+        // Values past u32::MAX, moved by more than i32::MAX in each direction
+        let test_input = String::from(
+            "seeds: 4294967300 5 7 2\n\nseed-to-soil map:\n10 4294967290 100\n6000000000 0 10\n\nsoil-to-location map:\n1 1 1",
+        );
+        let test_lines = &test_input;
+        assert_eq!(part1(&test_lines).unwrap(), 20);
+        assert_eq!(part2(test_lines).unwrap(), 20);
+
+        let overflowing = &String::from("seeds: 1\n\nseed-to-soil map:\n0 18446744073709551615 2");
+        let err = part1(overflowing).unwrap_err();
+        assert!(err.to_string().contains("runs past the largest value"));
+    }
+
+    #[test]
+    fn map_set_merges() {
+        let map = SeedMap::new(
+            "seed".to_owned(),
+            "soil".to_owned(),
+            &[
+                SeedRange::new(100, 0, 5).unwrap(),
+                SeedRange::new(15, 20, 5).unwrap(),
+            ],
+        )
+        .unwrap();
+        let seeds: IntervalSet<VALUE> = [HalfInterval::new(0, 10), HalfInterval::new(5, 20)]
+            .into_iter()
+            .collect();
+
+        // [5, 20) is untouched and [20, 25) moves down on top of part of it
+        let mapped = map.map_set(&seeds);
+        assert_eq!(
+            mapped,
+            IntervalSet::from_intervals([HalfInterval::new(5, 15), HalfInterval::new(100, 5)])
+        );
+        assert_eq!(seeds.n_intervals(), 1);
+        assert_eq!(mapped.total_length(), 20);
+    }
+
+    #[test]
+    fn composed_almanac() {
+        let parsed = Almanac::parse(EXAMPLE).unwrap();
+        let almanac = parsed.compose("seed", "location").unwrap().unwrap();
+        let by_hand = parsed
+            .maps
+            .iter()
+            .cloned()
+            .reduce(|a, m| a.then(&m))
+            .unwrap();
+        assert_eq!(almanac.map, by_hand.map);
+
+        assert_eq!(
+            (almanac.from.as_str(), almanac.to.as_str()),
+            ("seed", "location")
+        );
+        assert_eq!(
+            [79, 14, 55, 13].map(|s| almanac.map.get(s)),
+            [82, 43, 86, 35]
+        );
+
+        // Every map in the example moves values around without merging any, so it can be undone
+        let location_to_seed = almanac.map.inverse().unwrap();
+        assert_eq!(location_to_seed.get(46), 82);
+
+        // Soil 2 comes from both seed 2 and seed 12, which no one soil-to-seed map can give
+        let merging = Almanac::parse("seeds: 1\n\nseed-to-soil map:\n0 10 5").unwrap();
+        assert!(merging.compose("soil", "seed").unwrap().is_none());
+        let seeds = merging
+            .convert("soil", "seed", &HalfInterval::new(2, 1).into())
+            .unwrap();
+        assert_eq!(
+            seeds,
+            IntervalSet::from_intervals([HalfInterval::new(2, 1), HalfInterval::new(12, 1)])
+        );
+
+        let overlapping = &String::from("seeds: 1\n\nseed-to-soil map:\n0 10 5\n20 12 5");
+        let err = part1(overlapping).unwrap_err();
+        assert!(err.to_string().contains("overlap"));
+    }
+
+    #[test]
+    fn category_routes() {
+        // The maps in reverse order, which file order can't follow
+        let blocks: Vec<&str> = blocks(EXAMPLE).map(|(_, b)| b).collect();
+        let shuffled = [
+            &blocks[..1],
+            &blocks[1..].iter().rev().copied().collect::<Vec<_>>(),
+        ]
+        .concat()
+        .join("\n\n");
+        assert_eq!(part1(&shuffled).unwrap(), 35);
+        assert_eq!(part2(&shuffled).unwrap(), 46);
+
+        let almanac = Almanac::parse(&shuffled).unwrap();
+        assert_eq!(almanac.categories().len(), 8);
+
+        // Fertilizer 53 is water 49, light 42, temperature 42 and humidity 43
+        let fertilizer = IntervalSet::from(HalfInterval::new(53, 1));
+        let humidity = almanac
+            .convert("fertilizer", "humidity", &fertilizer)
+            .unwrap();
+        assert_eq!(humidity, IntervalSet::from(HalfInterval::new(43, 1)));
+        assert_eq!(
+            almanac
+                .convert("humidity", "fertilizer", &humidity)
+                .unwrap(),
+            fertilizer
+        );
+        let seeds = almanac
+            .convert("location", "seed", &HalfInterval::new(46, 1).into())
+            .unwrap();
+        assert!(seeds.contains(82));
+        assert_eq!(
+            almanac.convert("soil", "soil", &fertilizer).unwrap(),
+            fertilizer
+        );
+
+        // Without water-to-light, nothing joins seeds to locations
+        let partial: Vec<&str> = blocks
+            .iter()
+            .copied()
+            .filter(|b| !b.starts_with("water"))
+            .collect();
+        let err = part1(&partial.join("\n\n")).unwrap_err();
+        assert!(err.is::<NoRouteError>());
+        assert_eq!(err.to_string(), "no maps lead from seed to location");
+        let almanac = Almanac::parse(&partial.join("\n\n")).unwrap();
+        assert!(almanac.convert("light", "soil", &fertilizer).is_err());
+        assert!(almanac.convert("seed", "nowhere", &fertilizer).is_err());
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        // CRLF endings, extra blank lines between the maps and trailing ones at the end
+        let windows = EXAMPLE.replace("\n\n", "\n\n\n").replace('\n', "\r\n") + "\r\n\r\n";
+        assert_eq!(part1(&windows).unwrap(), 35);
+        assert_eq!(part2(&windows).unwrap(), 46);
+        assert_eq!(blocks(&windows).count(), 8);
+    }
 }
@@ -1,12 +1,24 @@
+use crate::common::{with_lines, NotImplementedError, ParseError};
+use crate::parsers::{key_value, parse_with, unsigned_list};
+use crate::solver::{Solver, SolverResult};
 use std::iter::zip;
 
-use nom::ParseTo;
+const DAY: u32 = 6;
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let times = parse_line(&lines[0]);
-    let dists = parse_line(&lines[1]);
+pub fn part1(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let (time_line, dist_line) = time_and_distance(lines)?;
+    let times = parse_line(0, time_line)?;
+    let dists = parse_line(1, dist_line)?;
+    if times.len() != dists.len() {
+        let msg = format!("expected {} distances, found {}", times.len(), dists.len());
+        return Err(ParseError::new(DAY, 1, 0, dist_line, msg));
+    }
 
-    return zip(times, dists)
+    return Ok(zip(times, dists)
         .map(|(t, l)| {
             let sqrt = (t * t - 4 as f64 * l).sqrt() / 2 as f64;
             let vertex = t / 2 as f64;
@@ -22,12 +34,17 @@ pub fn part1(lines: Vec<String>) -> u32 {
 
             return retval;
         })
-        .product();
+        .product());
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let t = parse_line_single(&lines[0]);
-    let l = parse_line_single(&lines[1]);
+pub fn part2(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let (time_line, dist_line) = time_and_distance(lines)?;
+    let t = parse_line_single(0, time_line)?;
+    let l = parse_line_single(1, dist_line)?;
 
     let sqrt = (t * t - 4 as f64 * l).sqrt() / 2 as f64;
     let vertex = t / 2 as f64;
@@ -41,16 +58,30 @@ pub fn part2(lines: Vec<String>) -> u32 {
         right - left + 1
     };
 
-    return retval;
+    return Ok(retval);
 }
 
-fn parse_line(l: &str) -> Vec<f64> {
-    let entries: Vec<Option<f64>> = l
-        .split(' ')
-        .map(|s| s.parse_to())
-        .filter(|o| *o != None)
-        .collect();
-    return entries.into_iter().map(|e| e.unwrap()).collect();
+fn time_and_distance<'a>(lines: &[&'a str]) -> Result<(&'a str, &'a str), ParseError> {
+    return match (lines.first(), lines.get(1)) {
+        (Some(t), Some(d)) => Ok((*t, *d)),
+        (Some(_), None) => Err(ParseError::new(DAY, 1, 0, "", "missing distances")),
+        (None, _) => Err(ParseError::new(DAY, 0, 0, "", "missing times")),
+    };
+}
+
+/// The numbers on a line after its `Label:`
+fn parse_line(idx: usize, l: &str) -> Result<Vec<f64>, ParseError> {
+    let numbers: Vec<u64> = parse_with(
+        DAY,
+        idx,
+        l,
+        |l| {
+            let (_, (_, entries)) = key_value(l)?;
+            unsigned_list(entries)
+        },
+        "expected '<label>: <numbers>'",
+    )?;
+    return Ok(numbers.into_iter().map(|v| v as f64).collect());
 }
 
 // Never ended up using this since I just went with floats in the end
@@ -67,25 +98,44 @@ fn parse_line(l: &str) -> Vec<f64> {
 //     }
 // }
 
-fn parse_line_single(l: &str) -> f64 {
-    let entries: Vec<&str> = l.split(' ').filter(|s| !s.is_empty()).collect();
-    let entries = entries[1..].to_vec();
+/// The number made by joining up all the digits on a line after its `Label:`
+fn parse_line_single(idx: usize, l: &str) -> Result<f64, ParseError> {
+    let (_, entries) = parse_with(DAY, idx, l, key_value, "expected '<label>:'")?;
+    let entry: String = entries.split_whitespace().collect();
+
+    return entry
+        .parse::<u64>()
+        .map(|v| v as f64)
+        .map_err(|_| ParseError::at(DAY, idx, l, entries, "invalid number"));
+}
 
-    let entry = entries.join("");
+pub struct Day6;
 
-    return entry.parse().unwrap();
+impl Solver for Day6 {
+    fn day(&self) -> u32 {
+        6
+    }
+    fn name(&self) -> &'static str {
+        "Wait For It"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let test_input: String = String::from("Time:      7  15   30\nDistance:  9  40  200");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 288);
     }
@@ -93,8 +143,8 @@ mod tests {
     #[test]
     fn part2_test() {
         let test_input: String = String::from("Time:      7  15   30\nDistance:  9  40  200");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 288);
     }
@@ -1,4 +1,8 @@
+use crate::common::{try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::solver::{Solver, SolverResult};
 use std::collections::hash_map::HashMap;
+use std::error::Error;
+use std::io::BufRead;
 use std::iter::zip;
 
 type FIVECARDS = [Card; 5];
@@ -67,8 +71,8 @@ impl HandType {
 }
 
 impl Card {
-    fn new(c: char) -> Card {
-        match c {
+    fn new(c: char) -> Option<Card> {
+        let card = match c {
             'W' => Card::Joker,
             '2' => Card::Two,
             '3' => Card::Three,
@@ -83,8 +87,9 @@ impl Card {
             'Q' => Card::Queen,
             'K' => Card::King,
             'A' => Card::Ace,
-            _ => panic!("Unrecognized card: {}", c),
-        }
+            _ => return None,
+        };
+        return Some(card);
     }
 }
 
@@ -182,20 +187,57 @@ fn replace_jokers(hand_set: &mut HashMap<Card, u32>) {
     };
 }
 
-pub fn part1(lines: Vec<String>) -> u32 {
-    let mut hands: Vec<Hand> = lines.iter().map(|l| parse_line(l)).collect();
-    hands.sort_by(|a, b| a.cmp(&b));
+const DAY: u32 = 7;
 
-    return hands
+pub fn part1(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let hands: Vec<Hand> = lines
         .iter()
         .enumerate()
-        .map(|(idx, h)| (idx + 1) as u32 * h.bid)
-        .sum();
+        .map(|(idx, l)| parse_line(idx, l, false))
+        .collect::<Result<_, _>>()?;
+
+    return Ok(winnings(hands));
+}
+
+pub fn part2(input: &str) -> Result<u32, ParseError> {
+    return with_lines(input, part2_lines);
+}
+
+pub fn part2_lines(lines: &[&str]) -> Result<u32, ParseError> {
+    let hands: Vec<Hand> = lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_line(idx, l, true))
+        .collect::<Result<_, _>>()?;
+
+    return Ok(winnings(hands));
+}
+
+/// `part1`, reading a hand at a time. Ranking needs every hand, so they're all kept, but as parsed
+///     hands rather than lines.
+pub fn part1_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    return Ok(winnings(stream_hands(reader, false)?));
+}
+
+/// `part2`, reading a hand at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<u32, Box<dyn Error>> {
+    return Ok(winnings(stream_hands(reader, true)?));
+}
+
+fn stream_hands(reader: impl BufRead, jokers: bool) -> Result<Vec<Hand>, Box<dyn Error>> {
+    return try_fold_lines(reader, vec![], |mut hands, idx, l| {
+        hands.push(parse_line(idx, l, jokers)?);
+        Ok(hands)
+    });
 }
 
-pub fn part2(lines: Vec<String>) -> u32 {
-    let mut hands: Vec<Hand> = lines.iter().map(|l| parse_line_joker(l)).collect();
-    hands.sort_by(|a, b| a.cmp(&b));
+/// Each hand's bid times its rank, weakest first
+fn winnings(mut hands: Vec<Hand>) -> u32 {
+    hands.sort();
 
     return hands
         .iter()
@@ -204,37 +246,72 @@ pub fn part2(lines: Vec<String>) -> u32 {
         .sum();
 }
 
-fn parse_line(l: &str) -> Hand {
-    let split_l: Vec<&str> = l.split(' ').collect();
+/// Parse a `<cards> <bid>` line, reading any `J` as a joker rather than a jack when `jokers` is set
+fn parse_line(idx: usize, l: &str, jokers: bool) -> Result<Hand, ParseError> {
+    let (cards, bid) = l
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(DAY, idx, 0, l, "expected '<cards> <bid>'"))?;
 
-    let hand: FIVECARDS = split_l[0]
+    let cards = cards
         .chars()
-        .into_iter()
-        .map(|c| Card::new(c))
-        .collect::<Vec<Card>>()
-        .try_into()
-        .unwrap();
+        .enumerate()
+        .map(|(col, c)| {
+            let c = if jokers && c == 'J' { 'W' } else { c };
+            Card::new(c).ok_or_else(|| ParseError::new(DAY, idx, col, l, "unrecognized card"))
+        })
+        .collect::<Result<Vec<Card>, ParseError>>()?;
+
+    let n_cards = cards.len();
+    let hand: FIVECARDS = cards.try_into().map_err(|_| {
+        let msg = format!("expected 5 cards, found {}", n_cards);
+        ParseError::new(DAY, idx, 0, l, msg)
+    })?;
 
-    let bid = split_l[1].parse::<u32>().unwrap();
+    let bid = bid
+        .parse::<u32>()
+        .map_err(|_| ParseError::at(DAY, idx, l, bid, "invalid bid"))?;
 
-    return Hand::new(hand.into(), bid);
+    return Ok(Hand::new(hand, bid));
 }
 
-fn parse_line_joker(l: &str) -> Hand {
-    return parse_line(&l.to_owned().replace("J", "W"));
+pub struct Day7;
+
+impl Solver for Day7 {
+    fn day(&self) -> u32 {
+        7
+    }
+    fn name(&self) -> &'static str {
+        "Camel Cards"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let test_input: String =
             String::from("32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483");
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 6440);
     }
@@ -243,9 +320,29 @@ mod tests {
     fn part2_test() {
         let test_input: String =
             String::from("32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483");
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 5905);
     }
+
+    #[test]
+    fn bad_hands() {
+        let err = part1("32T3K 765\nT55X5 684").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+
+        let err = part2("32T3 765").unwrap_err();
+        assert_eq!(err.message, "expected 5 cards, found 4");
+
+        assert!(part1("32T3K").is_err());
+    }
+
+    #[test]
+    fn streaming() {
+        let test_input = "32T3K 765\r\nT55J5 684\r\nKK677 28\r\nKTJJT 220\r\nQQQJA 483\r\n";
+
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 6440);
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 5905);
+        assert!(part1_stream("32T3K 765\nT55X5 684".as_bytes()).is_err());
+    }
 }
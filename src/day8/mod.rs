@@ -1,18 +1,15 @@
 #![allow(unused)]
 
-use std::collections::hash_map::HashMap;
+use crate::common::{input_lines, with_lines, NotImplementedError, ParseError};
+use crate::solver::{Solver, SolverResult};
+use std::collections::{HashMap, HashSet};
 
 use num::integer::lcm;
 
 type MAPID = (char, char, char);
 type MAP = HashMap<MAPID, MAPID>;
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::alpha1,
-    sequence::{separated_pair, tuple},
-    IResult,
-};
+use crate::parsers::{labelled_pair, parse_with};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 enum MapSide {
@@ -50,16 +47,22 @@ fn get_composite_map(cmds: Vec<MapSide>, left: MAP, right: MAP) -> MAP {
     return comp_map;
 }
 
-pub fn part1(lines: Vec<String>) -> u64 {
-    let binding = parse_map_sides(&lines[0]);
-    let mut commands = binding.iter().cycle();
+const DAY: u32 = 8;
 
-    let map_lines = lines[2..]
-        .iter()
-        .map(|l| parse_map_line(l).unwrap().1)
-        .collect();
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let (binding, map_lines) = parse_network(lines)?;
+    let mut commands = binding.iter().cycle();
 
     let (left_map, right_map, _) = get_maps(map_lines);
+    if !left_map.contains_key(&('A', 'A', 'A')) {
+        // Points at the first node, or the last line there is when there are none
+        let idx = 2.min(lines.len() - 1);
+        return Err(ParseError::new(DAY, idx, 0, lines[idx], "missing node AAA"));
+    }
 
     let mut cur_id = ('A', 'A', 'A');
     let mut ct = 0;
@@ -71,17 +74,15 @@ pub fn part1(lines: Vec<String>) -> u64 {
         ct += 1;
     }
 
-    return ct;
+    return Ok(ct);
 }
 
-pub fn part2(lines: Vec<String>) -> u64 {
-    let binding = parse_map_sides(&lines[0]);
-    let commands = binding.clone();
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    return with_lines(input, part2_lines);
+}
 
-    let map_lines = lines[2..]
-        .iter()
-        .map(|l| parse_map_line(l).unwrap().1)
-        .collect();
+pub fn part2_lines(lines: &[&str]) -> Result<u64, ParseError> {
+    let (commands, map_lines) = parse_network(lines)?;
 
     let (left_map, right_map, line_ids) = get_maps(map_lines);
 
@@ -95,7 +96,7 @@ pub fn part2(lines: Vec<String>) -> u64 {
         .into_iter()
         .map(|loc| find_first_z(loc, &commands, &left_map, &right_map))
         .reduce(|a, b| lcm(a, b))
-        .unwrap();
+        .ok_or_else(|| ParseError::new(DAY, lines.len(), 0, "", "no nodes ending in A"));
 
     // start_locs.into_iter().for_each(|loc| {
     //     cycle_n_times(loc, &commands, &left_map, &right_map, 200);
@@ -169,55 +170,106 @@ fn get_maps(lines: Vec<(MAPID, MAPID, MAPID)>) -> (MAP, MAP, Vec<MAPID>) {
     return (left_map, right_map, line_ids_return);
 }
 
-fn parse_map_sides(l: &str) -> Vec<MapSide> {
-    let results = l
+/// The directions on the first line, followed by the nodes from the third line on. Every node
+///     referenced must be defined, so that walking the network never leaves it.
+fn parse_network(lines: &[&str]) -> Result<(Vec<MapSide>, Vec<(MAPID, MAPID, MAPID)>), ParseError> {
+    let commands = match lines.first() {
+        Some(l) => parse_map_sides(l)?,
+        None => return Err(ParseError::new(DAY, 0, 0, "", "missing directions")),
+    };
+
+    let map_lines: Vec<(MAPID, MAPID, MAPID)> = lines
+        .iter()
+        .enumerate()
+        .skip(2)
+        .map(|(idx, l)| parse_map_line(idx, l))
+        .collect::<Result<_, _>>()?;
+
+    let defined: HashSet<MAPID> = map_lines.iter().map(|(id, _, _)| *id).collect();
+    for (offset, (_, left, right)) in map_lines.iter().enumerate() {
+        let idx = offset + 2;
+        if !defined.contains(left) {
+            return Err(ParseError::new(DAY, idx, 7, lines[idx], "undefined node"));
+        }
+        if !defined.contains(right) {
+            return Err(ParseError::new(DAY, idx, 12, lines[idx], "undefined node"));
+        }
+    }
+
+    return Ok((commands, map_lines));
+}
+
+fn parse_map_sides(l: &str) -> Result<Vec<MapSide>, ParseError> {
+    let results: Vec<MapSide> = l
         .chars()
-        .map(|c| match c {
-            'L' => Some(MapSide::Left),
-            'R' => Some(MapSide::Right),
-            _ => None,
+        .enumerate()
+        .map(|(col, c)| match c {
+            'L' => Ok(MapSide::Left),
+            'R' => Ok(MapSide::Right),
+            _ => Err(ParseError::new(DAY, 0, col, l, "expected L or R")),
         })
-        .map(|c| c.unwrap())
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    return results;
+    if results.is_empty() {
+        return Err(ParseError::new(DAY, 0, 0, l, "missing directions"));
+    }
+
+    return Ok(results);
 }
 
-fn parse_map_line(l: &str) -> IResult<&str, (MAPID, MAPID, MAPID)> {
-    let (rem, (id, _, (l, r))) = tuple((
-        str_to_mapid,
-        tag(" = ("),
-        separated_pair(str_to_mapid, tag(", "), str_to_mapid),
-    ))(l)?;
+fn parse_map_line(idx: usize, l: &str) -> Result<(MAPID, MAPID, MAPID), ParseError> {
+    let (id, (left, right)) =
+        parse_with(DAY, idx, l, labelled_pair, "expected 'XXX = (XXX, XXX)'")?;
 
-    return Ok((rem, (id, l, r)));
+    return Ok((
+        str_to_mapid(idx, l, id)?,
+        str_to_mapid(idx, l, left)?,
+        str_to_mapid(idx, l, right)?,
+    ));
 }
 
-fn str_to_mapid(s: &str) -> IResult<&str, MAPID> {
-    let (rem, res) = alpha1(s)?;
-    let mut s_iter = res.chars().into_iter();
+fn str_to_mapid(idx: usize, l: &str, s: &str) -> Result<MAPID, ParseError> {
+    return match s.chars().collect::<Vec<char>>()[..] {
+        [a, b, c] => Ok((a, b, c)),
+        _ => Err(ParseError::at(
+            DAY,
+            idx,
+            l,
+            s,
+            "node names are three characters",
+        )),
+    };
+}
 
-    let id = (
-        s_iter.next().unwrap(),
-        s_iter.next().unwrap(),
-        s_iter.next().unwrap(),
-    );
+pub struct Day8;
 
-    return Ok((rem, id));
+impl Solver for Day8 {
+    fn day(&self) -> u32 {
+        8
+    }
+    fn name(&self) -> &'static str {
+        "Haunted Wasteland"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let test_input: String = String::from(
             "RL\n\nAAA = (BBB, CCC)\nBBB = (DDD, EEE)\nCCC = (ZZZ, GGG)\nDDD = (DDD, DDD)\nEEE = (EEE, EEE)\nGGG = (GGG, GGG)\nZZZ = (ZZZ, ZZZ)",
         );
-        let test_lines = string_to_lines(&test_input);
-        let result = part1(test_lines);
+        let test_lines = &test_input;
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 2);
     }
@@ -226,9 +278,19 @@ mod tests {
         let test_input: String = String::from(
             "LR\n\nWWA = (WWB, XXX)\nWWB = (XXX, WWZ)\nWWZ = (WWB, XXX)\nSSA = (SSB, XXX)\nSSB = (SSC, SSC)\nSSC = (SSZ, SSZ)\nSSZ = (SSB, SSB)\nXXX = (XXX, XXX)",
         );
-        let test_lines = string_to_lines(&test_input);
-        let result = part2(test_lines);
+        let test_lines = &test_input;
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn missing_start() {
+        let err = part1("RL\n\nBBB = (BBB, BBB)").unwrap_err();
+        assert_eq!((err.line, err.message.as_str()), (3, "missing node AAA"));
+        assert_eq!(err.text, "BBB = (BBB, BBB)");
+
+        let err = part1("RL").unwrap_err();
+        assert_eq!((err.line, err.text.as_str()), (1, "RL"));
+    }
 }
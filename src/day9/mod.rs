@@ -1,29 +1,73 @@
 #![allow(unused)]
 
-pub fn part1(lines: Vec<String>) -> i64 {
-    let input = lines.iter().map(|l| parse_line(l));
-    let results = input.map(|invec| {
-        let dvecs = get_diff_vecs(&invec);
-        return propagate_diffs(dvecs, &invec);
-    });
+use std::error::Error;
+use std::io::BufRead;
+
+use crate::common::{input_lines, try_fold_lines, with_lines, NotImplementedError, ParseError};
+use crate::parsers::{parse_with, signed_list};
+use crate::solver::{Solver, SolverResult};
+
+const DAY: u32 = 9;
+
+pub fn part1(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part1_lines);
+}
+
+pub fn part1_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let input = parse_lines(lines)?;
+    return Ok(input.iter().map(next_value).sum());
+}
+
+pub fn part2(input: &str) -> Result<i64, ParseError> {
+    return with_lines(input, part2_lines);
+}
 
-    return results.sum();
+pub fn part2_lines(lines: &[&str]) -> Result<i64, ParseError> {
+    let input = parse_lines(lines)?;
+    return Ok(input.iter().map(previous_value).sum());
 }
 
-pub fn part2(lines: Vec<String>) -> i64 {
-    let input = lines.iter().map(|l| parse_line(l));
-    let results = input.map(|invec| {
-        let mut rvec = invec.to_owned();
-        rvec.reverse();
-        let dvecs = get_diff_vecs(&rvec);
-        return propagate_diffs(dvecs, &rvec);
+/// `part1`, reading a history at a time
+pub fn part1_stream(reader: impl BufRead) -> Result<i64, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        Ok(acc + next_value(&parse_line(idx, l)?))
     });
+}
+
+/// `part2`, reading a history at a time
+pub fn part2_stream(reader: impl BufRead) -> Result<i64, Box<dyn Error>> {
+    return try_fold_lines(reader, 0, |acc, idx, l| {
+        Ok(acc + previous_value(&parse_line(idx, l)?))
+    });
+}
+
+fn next_value(invec: &Vec<i64>) -> i64 {
+    let dvecs = get_diff_vecs(invec);
+    return propagate_diffs(dvecs, invec);
+}
+
+fn previous_value(invec: &Vec<i64>) -> i64 {
+    let mut rvec = invec.to_owned();
+    rvec.reverse();
+    return next_value(&rvec);
+}
 
-    return results.sum();
+fn parse_lines(lines: &[&str]) -> Result<Vec<Vec<i64>>, ParseError> {
+    return lines
+        .iter()
+        .enumerate()
+        .map(|(idx, l)| parse_line(idx, l))
+        .collect();
 }
 
-fn parse_line(l: &str) -> Vec<i64> {
-    return l.split(' ').map(|s| s.parse::<i64>().unwrap()).collect();
+fn parse_line(idx: usize, l: &str) -> Result<Vec<i64>, ParseError> {
+    return parse_with(
+        DAY,
+        idx,
+        l,
+        signed_list,
+        "expected numbers separated by spaces",
+    );
 }
 
 fn propagate_diffs(diffs: Vec<Vec<i64>>, v: &Vec<i64>) -> i64 {
@@ -93,27 +137,70 @@ where
     return true;
 }
 
+pub struct Day9;
+
+impl Solver for Day9 {
+    fn day(&self) -> u32 {
+        9
+    }
+    fn name(&self) -> &'static str {
+        "Mirage Maintenance"
+    }
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_lines(lines)?.into()),
+            2 => Ok(part2_lines(lines)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+    fn streams(&self) -> bool {
+        true
+    }
+    fn solve_stream(&self, reader: &mut dyn BufRead, part: u32) -> SolverResult {
+        return match part {
+            1 => Ok(part1_stream(reader)?.into()),
+            2 => Ok(part2_stream(reader)?.into()),
+            _ => Err(NotImplementedError.into()),
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::common::string_to_lines;
 
     #[test]
     fn part1_test() {
         let test_input = String::from("0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45");
-        let test_lines = string_to_lines(&test_input);
+        let test_lines = &test_input;
 
-        let result = part1(test_lines);
+        let result = part1(test_lines).unwrap();
 
         assert_eq!(result, 114);
     }
     #[test]
     fn part2_test() {
         let test_input = String::from("0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45");
-        let test_lines = string_to_lines(&test_input);
+        let test_lines = &test_input;
 
-        let result = part2(test_lines);
+        let result = part2(test_lines).unwrap();
 
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn streaming() {
+        let test_input = "0 3 6 9 12 15\n1 3 6 10 15 21\n10 13 16 21 30 45\n";
+
+        assert_eq!(part1_stream(test_input.as_bytes()).unwrap(), 114);
+        assert_eq!(part2_stream(test_input.as_bytes()).unwrap(), 2);
+    }
+
+    #[test]
+    fn bad_history() {
+        let err = part1("0 3 6 9\n1 3 x 10").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+
+        assert_eq!(part1("  -1  2 5 ").unwrap(), 8);
+    }
 }
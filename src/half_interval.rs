@@ -1,26 +1,59 @@
-use std::cmp::min;
+use std::cmp::{max, min};
+
+use num::PrimInt;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub struct HalfInterval {
+pub struct HalfInterval<T: PrimInt> {
     /// A half-open interval [lb, ub) over integers
     /// This means that [1, 2) only represents the digit 1
-    lb: u32,
-    range: u32,
+    /// The upper bound lb + range always fits in T
+    lb: T,
+    range: T,
 }
-impl HalfInterval {
-    pub fn new(lb: u32, range: u32) -> HalfInterval {
-        return HalfInterval { lb, range };
+impl<T: PrimInt> HalfInterval<T> {
+    /// Panics if the interval would run past the largest T, see `checked_new`
+    pub fn new(lb: T, range: T) -> HalfInterval<T> {
+        return HalfInterval::checked_new(lb, range)
+            .expect("interval runs past the largest value of its type");
+    }
+    /// None if the range is negative or the upper bound doesn't fit in T
+    pub fn checked_new(lb: T, range: T) -> Option<HalfInterval<T>> {
+        if range < T::zero() {
+            return None;
+        }
+        lb.checked_add(&range)?;
+        return Some(HalfInterval { lb, range });
     }
-    pub fn shift(&self, delta: i32) -> HalfInterval {
-        return HalfInterval::new((self.lb as i32 + delta) as u32, self.range);
+    /// The interval moved up by `n`, None if it would run past the largest T
+    pub fn shift_up(&self, n: T) -> Option<HalfInterval<T>> {
+        return HalfInterval::checked_new(self.lb.checked_add(&n)?, self.range);
     }
-    pub fn lb(&self) -> u32 {
+    /// The interval moved down by `n`, None if it would run past the smallest T
+    pub fn shift_down(&self, n: T) -> Option<HalfInterval<T>> {
+        return HalfInterval::checked_new(self.lb.checked_sub(&n)?, self.range);
+    }
+    pub fn lb(&self) -> T {
         self.lb
     }
-    pub fn delta(&self) -> u32 {
+    /// The first value past the interval
+    pub fn ub(&self) -> T {
+        self.lb + self.range
+    }
+    pub fn delta(&self) -> T {
         self.range
     }
-    pub fn intersect(&self, other: &HalfInterval) -> Option<HalfInterval> {
+    /// The interval [lb, ub), panics if ub is below lb
+    pub fn from_bounds(lb: T, ub: T) -> HalfInterval<T> {
+        assert!(lb <= ub, "interval bounds are the wrong way round");
+        return HalfInterval { lb, range: ub - lb };
+    }
+    pub fn is_empty(&self) -> bool {
+        self.range.is_zero()
+    }
+    pub fn contains(&self, v: T) -> bool {
+        return self.lb <= v && v < self.ub();
+    }
+    pub fn intersect(&self, other: &HalfInterval<T>) -> Option<HalfInterval<T>> {
         // Order the
         let (left, right) = if self.lb() < other.lb() {
             (self, other)
@@ -28,66 +61,433 @@ impl HalfInterval {
             (other, self)
         };
 
-        let dlb = right.lb() - left.lb();
-        let overlap = dlb < left.delta();
+        // Signed bounds far enough apart overflow, but then they're too far apart to overlap
+        let dlb = right.lb().checked_sub(&left.lb());
 
-        if overlap {
-            let left_end = right.lb();
-            let new_delta = min(left.delta() - dlb, right.delta());
-            return Some(HalfInterval::new(left_end, new_delta));
-        } else {
-            return None;
+        match dlb {
+            Some(dlb) if dlb < left.delta() && !right.is_empty() => {
+                let left_end = right.lb();
+                let new_delta = min(left.delta() - dlb, right.delta());
+                return Some(HalfInterval::new(left_end, new_delta));
+            }
+            _ => return None,
         }
     }
-    pub fn diff(&self, other: &HalfInterval) -> (Option<HalfInterval>, Option<HalfInterval>) {
-        // Compute the difference between self and other, i.e. self / other, read as
-        // self-remove-other in set terminology
+    /// Self with other removed, i.e. self / other in set terminology, as the part below other
+    ///     and the part above it. An empty other still splits self at its lower bound. Either
+    ///     part is None if it would be empty.
+    pub fn diff(
+        &self,
+        other: &HalfInterval<T>,
+    ) -> (Option<HalfInterval<T>>, Option<HalfInterval<T>>) {
+        let left = if self.lb() < other.lb() {
+            Some(HalfInterval::from_bounds(
+                self.lb(),
+                min(self.ub(), other.lb()),
+            ))
+        } else {
+            None
+        };
+        let right = if other.ub() < self.ub() {
+            Some(HalfInterval::from_bounds(
+                max(self.lb(), other.ub()),
+                self.ub(),
+            ))
+        } else {
+            None
+        };
+
+        let non_empty = |int: &HalfInterval<T>| !int.is_empty();
+        return (left.filter(non_empty), right.filter(non_empty));
+    }
+}
 
-        // Determine intersection, which determines what "self remove other" looks like
-        let inter = self.intersect(other);
+/// A set of integers stored as sorted, disjoint intervals. Touching or overlapping intervals are
+///     merged and empty ones dropped, so two sets holding the same values are always equal.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IntervalSet<T: PrimInt> {
+    intervals: Vec<HalfInterval<T>>,
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        return IntervalSet { intervals: vec![] };
+    }
+    pub fn from_intervals(intervals: impl IntoIterator<Item = HalfInterval<T>>) -> IntervalSet<T> {
+        let mut intervals: Vec<HalfInterval<T>> =
+            intervals.into_iter().filter(|i| !i.is_empty()).collect();
+        intervals.sort_by_key(|i| i.lb());
 
-        let retval = match inter {
-            None => {
-                if self.lb() < other.lb() {
-                    (Some(*self), None)
-                } else {
-                    (None, Some(*self))
+        let mut merged: Vec<HalfInterval<T>> = vec![];
+        for int in intervals {
+            match merged.last_mut() {
+                Some(last) if int.lb() <= last.ub() => {
+                    *last = HalfInterval::from_bounds(last.lb(), max(last.ub(), int.ub()));
                 }
+                _ => merged.push(int),
             }
-            Some(inter) => {
-                // The intersection is exact
-                if inter == *self {
-                    (None, None)
-                } else {
-                    let inter_lb = inter.lb();
-                    let left_delta = inter_lb - self.lb();
-                    let left = match left_delta {
-                        0 => None,
-                        v => Some(HalfInterval::new(self.lb, v + 1)),
-                    };
-
-                    let remaining_da = self.delta() - left_delta;
-                    let right = if remaining_da <= inter.delta() {
-                        None
-                    } else {
-                        // NOTE: THIS LINE MAY CAUSE ISSUES, MAYBE?
-                        let new_lb = inter_lb + inter.delta();
-                        let new_delta = remaining_da - inter.delta();
-                        Some(HalfInterval::new(new_lb, new_delta))
-                    };
-                    return (left, right);
-                    // (left, right)
+        }
+        return IntervalSet { intervals: merged };
+    }
+
+    pub fn insert(&mut self, int: HalfInterval<T>) {
+        *self = self.union(&IntervalSet::from(int));
+    }
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        return IntervalSet::from_intervals(self.iter().chain(other.iter()).copied());
+    }
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            if let Some(int) = a.intersect(&b) {
+                result.push(int);
+            }
+            // Whichever ends first can't overlap anything further along the other set
+            if a.ub() <= b.ub() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        return IntervalSet { intervals: result };
+    }
+    /// The values in `self` but not in `other`
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = vec![];
+        let mut j = 0;
+        for a in self.iter() {
+            while j < other.intervals.len() && other.intervals[j].ub() <= a.lb() {
+                j += 1;
+            }
+            // The gaps between the parts of `other` overlapping `a`. The last of those may also
+            //     overlap the next `a`, so `j` stays on the first.
+            let mut lb = a.lb();
+            for b in other.intervals[j..].iter().take_while(|b| b.lb() < a.ub()) {
+                if lb < b.lb() {
+                    result.push(HalfInterval::from_bounds(lb, b.lb()));
                 }
+                lb = max(lb, b.ub());
             }
-        };
+            if lb < a.ub() {
+                result.push(HalfInterval::from_bounds(lb, a.ub()));
+            }
+        }
+        return IntervalSet { intervals: result };
+    }
+    /// The values in `bounds` but not in the set
+    pub fn complement(&self, bounds: HalfInterval<T>) -> IntervalSet<T> {
+        return IntervalSet::from(bounds).difference(self);
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        // The only interval which could hold v is the last one starting at or before it
+        let idx = self.intervals.partition_point(|i| i.lb() <= v);
+        return idx > 0 && self.intervals[idx - 1].contains(v);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+    /// How many values are in the set
+    pub fn total_length(&self) -> T {
+        return self
+            .iter()
+            .fold(T::zero(), |total, int| total + int.delta());
+    }
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        return self.intervals.first().map(|i| i.lb());
+    }
+    /// The intervals in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = &HalfInterval<T>> {
+        return self.intervals.iter();
+    }
+    pub fn n_intervals(&self) -> usize {
+        self.intervals.len()
+    }
+}
 
-        return retval;
+impl<T: PrimInt> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: PrimInt> From<HalfInterval<T>> for IntervalSet<T> {
+    fn from(int: HalfInterval<T>) -> Self {
+        IntervalSet::from_intervals([int])
+    }
+}
+
+impl<T: PrimInt> FromIterator<HalfInterval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = HalfInterval<T>>>(iter: I) -> Self {
+        IntervalSet::from_intervals(iter)
+    }
+}
+
+impl<'a, T: PrimInt> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a HalfInterval<T>;
+    type IntoIter = std::slice::Iter<'a, HalfInterval<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.iter()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeSet;
+    use std::fmt::Debug;
+
+    const CASES: usize = 5000;
+
+    /// xorshift64, enough randomness for picking test cases without a dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            return self.0;
+        }
+        fn below(&mut self, n: u32) -> u32 {
+            return (self.next() % n as u64) as u32;
+        }
+        /// (lb, range) for a small interval, usually near 0 where intervals overlap often, but
+        ///     sometimes pressed up against u32::MAX
+        fn interval(&mut self) -> (u32, u32) {
+            let lb = if self.below(8) == 0 {
+                u32::MAX - self.below(64)
+            } else {
+                self.below(64)
+            };
+            let range = self.below(32).min(u32::MAX - lb);
+            return (lb, range);
+        }
+    }
+
+    /// Smaller versions of a failing case to try, each a single step closer to zero
+    trait Shrink: Sized {
+        fn shrink(&self) -> Vec<Self>;
+    }
+
+    impl Shrink for u32 {
+        fn shrink(&self) -> Vec<u32> {
+            let mut smaller = vec![0, self / 2, self.saturating_sub(1)];
+            smaller.retain(|v| v < self);
+            smaller.dedup();
+            return smaller;
+        }
+    }
+
+    impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+        fn shrink(&self) -> Vec<(A, B)> {
+            let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+            let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+            return firsts.chain(seconds).collect();
+        }
+    }
+
+    impl<T: Shrink + Clone> Shrink for Vec<T> {
+        fn shrink(&self) -> Vec<Vec<T>> {
+            // Drop an element, or shrink one in place
+            let dropped = (0..self.len()).map(|i| {
+                let mut v = self.clone();
+                v.remove(i);
+                v
+            });
+            let shrunk = (0..self.len()).flat_map(|i| {
+                self[i].shrink().into_iter().map(move |s| {
+                    let mut v = self.clone();
+                    v[i] = s;
+                    v
+                })
+            });
+            return dropped.chain(shrunk.collect::<Vec<_>>()).collect();
+        }
+    }
+
+    /// Check `property` on `CASES` cases from `generate`. On a failure, keep swapping the case for
+    ///     a smaller one that still fails, then panic with the smallest found.
+    fn check<C, G, P>(seed: u64, mut generate: G, property: P)
+    where
+        C: Shrink + Clone + Debug,
+        G: FnMut(&mut Rng) -> C,
+        P: Fn(&C) -> Result<(), String>,
+    {
+        let mut rng = Rng(seed);
+        for _ in 0..CASES {
+            let case = generate(&mut rng);
+            let Err(msg) = property(&case) else {
+                continue;
+            };
+
+            let (mut smallest, mut smallest_msg) = (case.clone(), msg);
+            while let Some((smaller, msg)) = smallest
+                .shrink()
+                .into_iter()
+                .find_map(|c| property(&c).err().map(|m| (c, m)))
+            {
+                (smallest, smallest_msg) = (smaller, msg);
+            }
+            panic!(
+                "failed for {:?} (shrunk from {:?}): {}",
+                smallest, case, smallest_msg
+            );
+        }
+    }
+
+    /// The shrinker can make invalid intervals, which are clamped to fit
+    fn interval((lb, range): (u32, u32)) -> HalfInterval<u32> {
+        return HalfInterval::new(lb, range.min(u32::MAX - lb));
+    }
+
+    fn values(int: &HalfInterval<u32>) -> BTreeSet<u32> {
+        return (int.lb()..int.ub()).collect();
+    }
+
+    fn set_values(set: &IntervalSet<u32>) -> BTreeSet<u32> {
+        return set.iter().flat_map(values).collect();
+    }
+
+    /// Some(interval) must hold exactly `expected`, which has to be non-empty
+    fn matches(got: Option<HalfInterval<u32>>, expected: &BTreeSet<u32>) -> Result<(), String> {
+        let got_values = got.as_ref().map(values).unwrap_or_default();
+        if got_values != *expected || (got.is_some() && expected.is_empty()) {
+            return Err(format!("got {:?}, expected {:?}", got, expected));
+        }
+        return Ok(());
+    }
+
+    #[test]
+    fn intersect_matches_sets() {
+        check(
+            1,
+            |rng| (rng.interval(), rng.interval()),
+            |(a, b)| {
+                let (a, b) = (interval(*a), interval(*b));
+                let expected = values(&a).intersection(&values(&b)).copied().collect();
+                return matches(a.intersect(&b), &expected);
+            },
+        );
+    }
+
+    #[test]
+    fn diff_matches_sets() {
+        check(
+            2,
+            |rng| (rng.interval(), rng.interval()),
+            |(a, b)| {
+                let (a, b) = (interval(*a), interval(*b));
+                let remaining: BTreeSet<u32> =
+                    values(&a).difference(&values(&b)).copied().collect();
+                let (left, right) = a.diff(&b);
+
+                // Left is whatever's below b, right whatever's above it
+                let below = remaining.iter().filter(|v| **v < b.lb()).copied().collect();
+                let above = remaining
+                    .iter()
+                    .filter(|v| **v >= b.lb())
+                    .copied()
+                    .collect();
+                matches(left, &below).map_err(|e| format!("left {}", e))?;
+                return matches(right, &above).map_err(|e| format!("right {}", e));
+            },
+        );
+    }
+
+    #[test]
+    fn shift_matches_sets() {
+        check(
+            3,
+            |rng| (rng.interval(), rng.below(128)),
+            |(a, n)| {
+                let a = interval(*a);
+                // Moving the bounds must stay in range, even for an empty interval
+                let up = a
+                    .ub()
+                    .checked_add(*n)
+                    .map(|_| values(&a).iter().map(|v| v + n).collect());
+                let down = a
+                    .lb()
+                    .checked_sub(*n)
+                    .map(|_| values(&a).iter().map(|v| v - n).collect());
+
+                for (name, got, expected) in
+                    [("up", a.shift_up(*n), up), ("down", a.shift_down(*n), down)]
+                {
+                    match (got, expected) {
+                        (None, None) => (),
+                        (Some(got), Some(expected)) => {
+                            if values(&got) != expected || got.delta() != a.delta() {
+                                return Err(format!("shift {} gave {:?}", name, got));
+                            }
+                        }
+                        (got, expected) => {
+                            return Err(format!(
+                                "shift {} gave {:?}, expected {:?}",
+                                name, got, expected
+                            ))
+                        }
+                    }
+                }
+                return Ok(());
+            },
+        );
+    }
+
+    #[test]
+    fn interval_set_matches_sets() {
+        let sets = |rng: &mut Rng| {
+            let n = rng.below(5) as usize;
+            return (0..n).map(|_| rng.interval()).collect::<Vec<_>>();
+        };
+        check(
+            4,
+            |rng| (sets(rng), sets(rng)),
+            |(a, b)| {
+                let a: IntervalSet<u32> = a.iter().map(|i| interval(*i)).collect();
+                let b: IntervalSet<u32> = b.iter().map(|i| interval(*i)).collect();
+                let (va, vb) = (set_values(&a), set_values(&b));
+
+                // Normalised: sorted, non-empty, and with a gap between each interval
+                for (x, y) in a.iter().zip(a.iter().skip(1)) {
+                    if x.is_empty() || x.ub() >= y.lb() {
+                        return Err(format!("{:?} isn't normalised", a));
+                    }
+                }
+
+                let ops = [
+                    ("union", a.union(&b), va.union(&vb).copied().collect()),
+                    (
+                        "intersection",
+                        a.intersection(&b),
+                        va.intersection(&vb).copied().collect(),
+                    ),
+                    (
+                        "difference",
+                        a.difference(&b),
+                        va.difference(&vb).copied().collect(),
+                    ),
+                ];
+                for (name, got, expected) in ops {
+                    if set_values(&got) != expected {
+                        return Err(format!("{} gave {:?}", name, got));
+                    }
+                }
+                if a.total_length() as usize != va.len() {
+                    return Err(format!("total length {} of {:?}", a.total_length(), a));
+                }
+                if let Some(v) = (0..70).find(|v| a.contains(*v) != va.contains(v)) {
+                    return Err(format!("contains({}) is wrong for {:?}", v, a));
+                }
+                return Ok(());
+            },
+        );
+    }
 
     #[test]
     fn test_int() {
@@ -105,9 +505,76 @@ mod tests {
 
         let (l, r) = a.diff(&b);
 
-        assert_eq!(HalfInterval::new(1, 3), l.unwrap());
+        assert_eq!(HalfInterval::new(1, 2), l.unwrap());
         assert_eq!(HalfInterval::new(5, 5), r.unwrap());
 
         assert_eq!((None, None), a.diff(&a));
     }
+
+    #[test]
+    fn test_wide() {
+        let big: u64 = u32::MAX as u64 + 10;
+        let a = HalfInterval::new(big, 100);
+        assert_eq!(a.ub(), big + 100);
+        assert_eq!(a.shift_down(big).unwrap(), HalfInterval::new(0, 100));
+        assert_eq!(a.shift_down(big + 1), None);
+        assert_eq!(a.shift_up(u64::MAX - big - 100).unwrap().ub(), u64::MAX);
+        assert_eq!(a.shift_up(u64::MAX - big - 99), None);
+
+        assert_eq!(HalfInterval::checked_new(u64::MAX, 1), None);
+        assert_eq!(HalfInterval::checked_new(0i64, -1), None);
+        let signed = HalfInterval::new(-5i64, 10);
+        assert_eq!(signed.shift_down(i64::MAX - 10).unwrap().lb(), i64::MIN + 6);
+        assert_eq!(signed.shift_down(i64::MAX), None);
+        assert_eq!(signed.intersect(&HalfInterval::new(i64::MAX - 1, 1)), None);
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let set: IntervalSet<u32> = [
+            HalfInterval::new(10, 5),
+            HalfInterval::new(0, 3),
+            HalfInterval::new(3, 2),
+            HalfInterval::new(12, 8),
+            HalfInterval::new(30, 0),
+        ]
+        .into_iter()
+        .collect();
+        // [0, 5) and [10, 20), with the touching and overlapping pieces merged
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec![HalfInterval::new(0, 5), HalfInterval::new(10, 10)]
+        );
+        assert_eq!(set.total_length(), 15);
+        assert_eq!(set.min(), Some(0));
+        assert!(set.contains(4) && set.contains(10) && set.contains(19));
+        assert!(!set.contains(5) && !set.contains(20) && !set.contains(30));
+
+        let other =
+            IntervalSet::from_intervals([HalfInterval::new(4, 8), HalfInterval::new(18, 10)]);
+        assert_eq!(
+            set.intersection(&other),
+            IntervalSet::from_intervals([
+                HalfInterval::new(4, 1),
+                HalfInterval::new(10, 2),
+                HalfInterval::new(18, 2)
+            ])
+        );
+        assert_eq!(
+            set.difference(&other),
+            IntervalSet::from_intervals([HalfInterval::new(0, 4), HalfInterval::new(12, 6)])
+        );
+        assert_eq!(set.union(&other).n_intervals(), 1);
+        assert_eq!(set.union(&other).total_length(), 28);
+        assert_eq!(
+            set.complement(HalfInterval::new(0, 25)),
+            IntervalSet::from_intervals([HalfInterval::new(5, 5), HalfInterval::new(20, 5)])
+        );
+
+        let mut grown = IntervalSet::new();
+        grown.insert(HalfInterval::new(5, 5));
+        grown.insert(HalfInterval::new(0, 5));
+        assert_eq!(grown, IntervalSet::from(HalfInterval::new(0, 10)));
+        assert!(IntervalSet::<u32>::new().difference(&set).is_empty());
+    }
 }
@@ -17,7 +17,15 @@ pub mod day7;
 pub mod day8;
 pub mod day9;
 
+pub mod answers;
+pub mod cli;
 pub mod common;
 pub mod half_interval;
 pub mod matrix;
 pub mod parsers;
+pub mod report;
+pub mod runner;
+pub mod search;
+pub mod shift_map;
+pub mod solver;
+pub mod sparse_grid;
@@ -1,145 +1,354 @@
 #![allow(unused)]
 
 use std::error;
-use std::fs::read_to_string;
+use std::fs::{read_to_string, File};
+use std::io::{self, BufRead, BufReader, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 
 use advent_of_code_23::{
-    common::{Cli, NoInputError, NotImplementedError},
-    *,
+    answers::Answers,
+    cli::Cli,
+    common::{
+        read_input, CheckFailedError, IncompleteRunError, InputError, InvalidCrucibleError,
+        NoInputError, NotImplementedError, NotStreamableError, ParseError,
+    },
+    day17::CustomCrucible,
+    report::{fmt_duration, format_json, format_table, format_tsv, OutputFormat},
+    runner::{
+        run_days, run_files, run_input, run_stream, unreadable_input, DayRange, Outcome, RunResult,
+    },
+    solver::{find_solver, registry},
 };
 
-fn main() -> Result<(), Box<dyn error::Error>> {
+type READER<'a> = Result<Box<dyn BufRead + 'a>, InputError>;
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    // TODO: Probably better way to handle the "no input" than the cascaded Nones here
-    let input = if let Some(p) = cli.file {
-        match read_to_string(p) {
-            Ok(s) => Some(s),
-            Err(_) => None,
+    // Report errors through Display, so e.g. IO errors read as a message with their path.
+    //     Malformed input has already been reported with a diagnostic, where its path was known.
+    return match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if !e.is::<ParseError>() {
+                eprintln!("Error: {}", e);
+            }
+            ExitCode::FAILURE
         }
-    } else if let Some(cli_input) = cli.input {
-        Some(cli_input)
+    };
+}
+
+fn run(cli: &Cli) -> Result<(), Box<dyn error::Error>> {
+    if cli.list {
+        list_solvers();
+        return Ok(());
+    }
+
+    let answers = match &cli.check {
+        Some(p) => Some(read_to_string(p)?.parse::<Answers>()?),
+        None => None,
+    };
+
+    if cli.crucible.is_set() && cli.day != Some(17) {
+        let msg = "the crucible options only apply to --day 17".to_string();
+        return Err(InvalidCrucibleError(msg).into());
+    }
+
+    if cli.all || cli.days.is_some() {
+        let days = cli.days.clone().unwrap_or_else(DayRange::all);
+        let bench = cli.bench.map(|n| n as usize);
+        let results = run_days(&days, cli.part, &cli.input_dir, bench);
+        return report_results(&results, cli, answers.as_ref());
+    }
+
+    if let (Some(answers), None) = (&answers, cli.day) {
+        // Only checking, so run everything we have an answer for
+        let bench = cli.bench.map(|n| n as usize);
+        let results: Vec<RunResult> = answers
+            .entries()
+            .flat_map(|(d, p)| run_days(&DayRange::new(d, d), Some(p), &cli.input_dir, bench))
+            .collect();
+        return report_results(&results, cli, Some(answers));
+    }
+
+    // Required by clap unless we're listing, checking or running many days
+    let day = match cli.day {
+        Some(day) => day,
+        None => return Err(missing_arg("--day").into()),
+    };
+
+    let solver = match find_solver(day) {
+        Some(_) if day == 17 && cli.crucible.is_set() => Box::new(CustomCrucible {
+            args: cli.crucible.clone(),
+        }),
+        Some(s) => s,
+        None => return Err(NotImplementedError.into()),
+    };
+
+    // Only --check lets the part be left out, in which case every part of the day is checked
+    let parts = match cli.part {
+        Some(part) => vec![part],
+        None if answers.is_some() => solver.parts(),
+        None => return Err(missing_arg("--part").into()),
+    };
+
+    let results: Vec<RunResult> = if cli.stream {
+        if !solver.streams() {
+            return Err(NotStreamableError(day).into());
+        }
+        // Clap requires --part with --stream, as a reader can only be gone through once
+        let mut results = vec![];
+        for (path, reader) in gather_readers(cli)? {
+            results.push(match reader {
+                Ok(reader) => run_stream(solver.as_ref(), reader, path.as_deref(), parts[0]),
+                Err(e) => unreadable_input(day, parts[0], &e),
+            });
+        }
+        results
+    } else if !cli.file.is_empty() {
+        let bench = cli.bench.map(|n| n as usize);
+        run_files(solver.as_ref(), &cli.file, &parts, bench)
     } else {
-        None
+        let bench = cli.bench.map(|n| n as usize);
+        let (path, input) = gather_input(cli)?;
+        parts
+            .iter()
+            .map(|part| run_input(solver.as_ref(), &input, path.as_deref(), *part, bench))
+            .collect()
+    };
+
+    if cli.format != OutputFormat::Text {
+        return report_results(&results, cli, answers.as_ref());
+    }
+
+    // One input failing shouldn't hide the answers for the rest
+    let mut errors = vec![];
+    for result in results.iter() {
+        if let Err(e) = print_answer(result, cli.time) {
+            errors.push(e);
+        }
+    }
+
+    if let Some(answers) = &answers {
+        check_answers(&results, answers, cli)?;
+    }
+
+    if errors.len() > 1 {
+        for e in errors.iter().filter(|e| !e.is::<ParseError>()) {
+            eprintln!("Error: {}", e);
+        }
+        return Err(IncompleteRunError {
+            failed: errors.len(),
+            total: results.len(),
+        }
+        .into());
+    }
+
+    return match errors.pop() {
+        Some(e) => Err(e),
+        None => Ok(()),
     };
+}
+
+/// The input to run when no files are given, paired with `-` if it came from piped stdin
+fn gather_input(cli: &Cli) -> Result<(Option<PathBuf>, String), Box<dyn error::Error>> {
+    if let Some(cli_input) = &cli.input {
+        return Ok((None, cli_input.clone()));
+    }
 
-    let day = cli.day;
-    let part = cli.part;
+    if !io::stdin().is_terminal() {
+        let stdin = PathBuf::from("-");
+        let input = read_input(&stdin)?;
+        return Ok((Some(stdin), input));
+    }
+
+    return Err(NoInputError.into());
+}
 
-    let result: Box<dyn std::fmt::Display> = match input {
-        Some(instring) => entry(instring, day, part)?,
-        None => return Err(NoInputError.into()),
+/// Every input to run, opened to be read as it's solved rather than read all at once. Files are
+///     opened in the order given, each keeping its own error if it can't be.
+fn gather_readers(cli: &Cli) -> Result<Vec<(Option<PathBuf>, READER<'_>)>, Box<dyn error::Error>> {
+    let open = |p: &PathBuf| -> Result<Box<dyn BufRead>, InputError> {
+        if p == Path::new("-") {
+            return Ok(Box::new(io::stdin().lock()));
+        }
+        let file = File::open(p).map_err(|source| InputError {
+            path: p.clone(),
+            source,
+        })?;
+        return Ok(Box::new(BufReader::new(file)));
     };
 
-    println!("Answer for day {}, part {}", day, part);
-    println!("{}", result);
+    if !cli.file.is_empty() {
+        return Ok(cli
+            .file
+            .iter()
+            .map(|p| (Some(p.clone()), open(p)))
+            .collect());
+    }
+
+    if let Some(cli_input) = &cli.input {
+        return Ok(vec![(None, Ok(Box::new(cli_input.as_bytes())))]);
+    }
+
+    if !io::stdin().is_terminal() {
+        let stdin = PathBuf::from("-");
+        let reader = open(&stdin);
+        return Ok(vec![(Some(stdin), reader)]);
+    }
+
+    return Err(NoInputError.into());
+}
+
+fn missing_arg(arg: &str) -> clap::Error {
+    return Cli::command().error(
+        clap::error::ErrorKind::MissingRequiredArgument,
+        format!("{} is required", arg),
+    );
+}
+
+fn print_answer(result: &RunResult, show_time: bool) -> Result<(), Box<dyn error::Error>> {
+    let answer = match &result.outcome {
+        Outcome::Answer(a) => a,
+        Outcome::Invalid(e) => {
+            print_diagnostic(result, e);
+            return Err(e.clone().into());
+        }
+        Outcome::Failed(e) => return Err(e.clone().into()),
+        Outcome::MissingInput(_) => return Err(NoInputError.into()),
+    };
+
+    let source = match result.input.as_ref().and_then(|i| i.path.as_ref()) {
+        Some(p) if p == Path::new("-") => " (stdin)".to_string(),
+        Some(p) => format!(" ({})", p.display()),
+        None => String::new(),
+    };
+
+    println!(
+        "Answer for day {}, part {}{}",
+        result.day, result.part, source
+    );
+    println!("{}", answer);
+
+    if let (true, Some(timing)) = (show_time, result.timing) {
+        match timing.split {
+            Some(split) => println!(
+                "Split: {}, Solve: {}",
+                fmt_duration(split),
+                fmt_duration(timing.solve)
+            ),
+            None => println!("Solve: {}", fmt_duration(timing.solve)),
+        }
+    }
+    if let Some(stats) = result.bench {
+        println!(
+            "Bench over {} runs: min {}, median {}, max {}",
+            stats.runs,
+            fmt_duration(stats.min),
+            fmt_duration(stats.median),
+            fmt_duration(stats.max)
+        );
+    }
 
     return Ok(());
 }
 
-fn entry(
-    instr: String,
-    day: u32,
-    part: u32,
-) -> Result<Box<dyn std::fmt::Display>, Box<dyn error::Error>> {
-    let lines = common::string_to_lines(&instr);
-
-    // TODO: Is there a better way of structuring this?
-    let result: Box<dyn std::fmt::Display> = match day {
-        1 => Box::new(match part {
-            1 => day1::part1(lines),
-            2 => day1::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        2 => Box::new(match part {
-            1 => day2::part1(lines),
-            2 => day2::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        3 => Box::new(match part {
-            1 => day3::part1(lines),
-            2 => day3::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        4 => Box::new(match part {
-            1 => day4::part1(lines),
-            2 => day4::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        5 => Box::new(match part {
-            1 => day5::part1(lines),
-            2 => day5::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        6 => Box::new(match part {
-            1 => day6::part1(lines),
-            2 => day6::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        7 => Box::new(match part {
-            1 => day7::part1(lines),
-            2 => day7::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        8 => Box::new(match part {
-            1 => day8::part1(lines),
-            2 => day8::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        9 => Box::new(match part {
-            1 => day9::part1(lines),
-            2 => day9::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        10 => Box::new(match part {
-            1 => day10::part1(lines),
-            2 => day10::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        11 => Box::new(match part {
-            1 => day11::part1(lines),
-            2 => day11::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        12 => Box::new(match part {
-            1 => day12::part1(lines),
-            2 => day12::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        13 => Box::new(match part {
-            1 => day13::part1(lines),
-            2 => day13::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        14 => Box::new(match part {
-            1 => day14::part1(lines),
-            2 => day14::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        15 => Box::new(match part {
-            1 => day15::part1(lines),
-            2 => day15::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        16 => Box::new(match part {
-            1 => day16::part1(lines),
-            2 => day16::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        17 => Box::new(match part {
-            1 => day17::part1(lines),
-            2 => day17::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        18 => Box::new(match part {
-            1 => day18::part1(lines),
-            2 => day18::part2(lines),
-            _ => return Err(NotImplementedError.into()),
-        }),
-        _ => return Err(NotImplementedError.into()),
+/// Point at malformed input on stderr, keeping stdout parseable for structured output
+fn print_diagnostic(result: &RunResult, e: &ParseError) {
+    let path = result.input.as_ref().and_then(|i| i.path.as_deref());
+    eprintln!("{}", e.render(path));
+}
+
+fn list_solvers() {
+    for solver in registry() {
+        let parts: Vec<String> = solver.parts().iter().map(|p| p.to_string()).collect();
+        println!(
+            "Day {:>2}: {} (parts {})",
+            solver.day(),
+            solver.name(),
+            parts.join(", ")
+        );
+    }
+}
+
+fn report_results(
+    results: &[RunResult],
+    cli: &Cli,
+    answers: Option<&Answers>,
+) -> Result<(), Box<dyn error::Error>> {
+    let output = match cli.format {
+        OutputFormat::Text => format_table(results, cli.time),
+        OutputFormat::Json => format_json(results),
+        OutputFormat::Tsv => format_tsv(results),
+    };
+    println!("{}", output);
+
+    // Both parts usually trip over the same line, which only needs pointing out once
+    let mut reported: Vec<&ParseError> = vec![];
+    for r in results {
+        if let Outcome::Invalid(e) = &r.outcome {
+            if !reported.contains(&e) {
+                eprintln!();
+                print_diagnostic(r, e);
+                reported.push(e);
+            }
+        }
+    }
+
+    if let Some(answers) = answers {
+        check_answers(results, answers, cli)?;
+    }
+
+    let failed = results.iter().filter(|r| !r.succeeded()).count();
+    if failed > 0 {
+        return Err(IncompleteRunError {
+            failed,
+            total: results.len(),
+        }
+        .into());
+    }
+
+    return Ok(());
+}
+
+fn check_answers(
+    results: &[RunResult],
+    answers: &Answers,
+    cli: &Cli,
+) -> Result<(), Box<dyn error::Error>> {
+    // Known answers for anything this run was asked for, but didn't produce, fail too
+    let in_scope = |day: u32, part: u32| {
+        let day_wanted = match (&cli.days, cli.day) {
+            _ if cli.all => true,
+            (Some(days), _) => days.contains(day),
+            (None, Some(only)) => day == only,
+            (None, None) => true,
+        };
+        return day_wanted && cli.part.map_or(true, |only| only == part);
+    };
+    let mismatches = answers.check(results, in_scope);
+
+    // Keep stdout parseable when emitting structured output
+    let report: Box<dyn Fn(String)> = match cli.format {
+        OutputFormat::Text => Box::new(|s| println!("{}", s)),
+        _ => Box::new(|s| eprintln!("{}", s)),
     };
 
-    return Ok(result);
+    if mismatches.is_empty() {
+        report("All checked answers match".to_string());
+        return Ok(());
+    }
+
+    report(format!("\n{} answers did not match:", mismatches.len()));
+    for m in mismatches.iter() {
+        report(m.to_string());
+    }
+
+    return Err(CheckFailedError {
+        mismatches: mismatches.len(),
+    }
+    .into());
 }
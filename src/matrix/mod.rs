@@ -0,0 +1,363 @@
+#![allow(dead_code)]
+
+use core::fmt;
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+mod transform;
+mod views;
+mod window;
+
+pub use transform::Orientation;
+pub use views::{ColView, ColViewMut, LineIter, LineView, LineViewMut, RowView, RowViewMut};
+pub use window::Window;
+
+/// A cardinal direction on a grid indexed by (row, col), so North is towards row 0 and West
+///     towards column 0
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    /// Clockwise from North
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Quarter turn anticlockwise
+    pub fn left(&self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+            Direction::West => Direction::South,
+        }
+    }
+    /// Quarter turn clockwise
+    pub fn right(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::South => Direction::West,
+            Direction::East => Direction::South,
+            Direction::West => Direction::North,
+        }
+    }
+    pub fn reverse(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+    /// Change in (row, col) from a single step
+    pub fn delta(&self) -> (i64, i64) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
+            Direction::East => (0, 1),
+            Direction::West => (0, -1),
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::North => write!(f, "↑"),
+            Direction::South => write!(f, "↓"),
+            Direction::East => write!(f, "→"),
+            Direction::West => write!(f, "←"),
+        }
+    }
+}
+
+/// Offsets of the 8 surrounding cells, clockwise from North
+const NEIGHBOURS_8: [(i64, i64); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    pub fn from_rows(mut row_data: Vec<Vec<T>>) -> AoCMatrix<T> {
+        let rows = row_data.len();
+        let cols = row_data[0].len();
+
+        let data = row_data
+            .iter_mut()
+            .reduce(|a, b| {
+                a.append(b);
+                a
+            })
+            .unwrap()
+            .to_vec();
+
+        return AoCMatrix { data, rows, cols };
+    }
+    pub fn filled_matrix(x: T, rows: usize, cols: usize) -> AoCMatrix<T> {
+        let data = vec![x; rows * cols];
+        return AoCMatrix { data, rows, cols };
+    }
+    /// Build a matrix by calling `f` with each (row, col) position in row-major order
+    pub fn from_fn<F>(rows: usize, cols: usize, mut f: F) -> AoCMatrix<T>
+    where
+        F: FnMut((usize, usize)) -> T,
+    {
+        let data = (0..rows)
+            .cartesian_product(0..cols)
+            .map(|pos| f(pos))
+            .collect();
+        return AoCMatrix { data, rows, cols };
+    }
+    pub fn get_data(&self) -> &Vec<T> {
+        &self.data
+    }
+    pub fn n_rows(&self) -> usize {
+        self.rows
+    }
+    pub fn n_cols(&self) -> usize {
+        self.cols
+    }
+    pub fn in_mat(&self, pos: (usize, usize)) -> bool {
+        return pos.0 < self.rows && pos.1 < self.cols;
+    }
+
+    /// Position `n` steps from `pos` in `dir`, or None if that's off the matrix
+    pub fn step(&self, pos: (usize, usize), dir: Direction, n: usize) -> Option<(usize, usize)> {
+        let (dr, dc) = dir.delta();
+        return self.offset(pos, (dr * n as i64, dc * n as i64));
+    }
+
+    /// Position shifted by a signed (row, col) offset, or None if that's off the matrix
+    pub fn offset(&self, pos: (usize, usize), delta: (i64, i64)) -> Option<(usize, usize)> {
+        let row = pos.0.checked_add_signed(delta.0.try_into().ok()?)?;
+        let col = pos.1.checked_add_signed(delta.1.try_into().ok()?)?;
+
+        return if self.in_mat((row, col)) {
+            Some((row, col))
+        } else {
+            None
+        };
+    }
+
+    /// The (up to) 4 orthogonally adjacent positions which are on the matrix, clockwise from North
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        return Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(pos, d, 1));
+    }
+
+    /// The (up to) 8 surrounding positions, diagonals included, which are on the matrix, clockwise
+    ///     from North
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        return NEIGHBOURS_8
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta));
+    }
+
+    /// Every cell with its (row, col) position, in row-major order
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let cols = self.cols;
+        return self
+            .data
+            .iter()
+            .enumerate()
+            .map(move |(idx, v)| ((idx / cols, idx % cols), v));
+    }
+    pub fn indexed_iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        let cols = self.cols;
+        return self
+            .data
+            .iter_mut()
+            .enumerate()
+            .map(move |(idx, v)| ((idx / cols, idx % cols), v));
+    }
+
+    pub fn row(&self, r: usize) -> RowView<'_, T> {
+        assert!(r < self.rows, "row {} out of range", r);
+        return LineView::new(&self.data, r * self.cols, 1, self.cols);
+    }
+    pub fn col(&self, c: usize) -> ColView<'_, T> {
+        assert!(c < self.cols, "column {} out of range", c);
+        return LineView::new(&self.data, c, self.cols, self.rows);
+    }
+    pub fn row_mut(&mut self, r: usize) -> RowViewMut<'_, T> {
+        assert!(r < self.rows, "row {} out of range", r);
+        return LineViewMut::new(&mut self.data, r * self.cols, 1, self.cols);
+    }
+    pub fn col_mut(&mut self, c: usize) -> ColViewMut<'_, T> {
+        assert!(c < self.cols, "column {} out of range", c);
+        return LineViewMut::new(&mut self.data, c, self.cols, self.rows);
+    }
+
+    /// Views of each row, top to bottom
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = RowView<'_, T>> + ExactSizeIterator {
+        return (0..self.rows).map(|r| self.row(r));
+    }
+    /// Views of each column, left to right
+    pub fn cols(&self) -> impl DoubleEndedIterator<Item = ColView<'_, T>> + ExactSizeIterator {
+        return (0..self.cols).map(|c| self.col(c));
+    }
+    /// Mutable views of each row, top to bottom. Columns overlap in memory, so they can only be
+    ///     borrowed mutably one at a time through `col_mut`.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = RowViewMut<'_, T>> {
+        let cols = self.cols;
+        return self
+            .data
+            .chunks_mut(cols.max(1))
+            .map(move |c| LineViewMut::new(c, 0, 1, cols));
+    }
+}
+
+impl<T: Copy> AoCMatrix<T> {
+    pub fn rows_by_value(&self) -> Vec<Vec<T>> {
+        return self.rows().map(|r| r.iter().copied().collect()).collect();
+    }
+
+    pub fn cols_by_value(&self) -> Vec<Vec<T>> {
+        return self.cols().map(|c| c.iter().copied().collect()).collect();
+    }
+}
+
+impl<T> Index<(usize, usize)> for AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    type Output = T;
+
+    fn index(&self, pair: (usize, usize)) -> &Self::Output {
+        let (row, col) = pair;
+        let cols = self.cols;
+        let idx = row * cols + col;
+
+        return &self.data[idx];
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    fn index_mut(&mut self, pair: (usize, usize)) -> &mut Self::Output {
+        let (row, col) = pair;
+        let cols = self.cols;
+        let idx = row * cols + col;
+
+        return &mut self.data[idx];
+    }
+}
+
+impl<T: Clone + Display> Display for AoCMatrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.rows()
+                .map(|r| r.iter().map(|c| c.to_string()).join(""))
+                .join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AoCMatrix, Direction};
+    use std::collections::HashSet;
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<Vec<u64>> = (0..10)
+            .collect::<Vec<u64>>()
+            .chunks(2)
+            .into_iter()
+            .map(|c| c.to_vec())
+            .collect();
+
+        let mat = AoCMatrix::from_rows(data);
+
+        assert_eq!(mat.rows, 5);
+        assert_eq!(mat.cols, 2);
+
+        assert_eq!(mat[(0, 0)], 0);
+        assert_eq!(mat[(2, 1)], 5);
+    }
+
+    #[test]
+    fn stepping() {
+        let mat = AoCMatrix::filled_matrix(0, 3, 4);
+
+        assert_eq!(mat.step((1, 1), Direction::North, 1), Some((0, 1)));
+        assert_eq!(mat.step((1, 1), Direction::North, 2), None);
+        assert_eq!(mat.step((1, 1), Direction::East, 2), Some((1, 3)));
+        assert_eq!(mat.step((0, 0), Direction::West, 1), None);
+        assert_eq!(mat.step((2, 3), Direction::South, 1), None);
+
+        assert_eq!(
+            mat.neighbours4((0, 0)).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(mat.neighbours8((0, 0)).count(), 3);
+        assert_eq!(mat.neighbours8((1, 1)).count(), 8);
+
+        let positions: Vec<(usize, usize)> = mat.indexed_iter().map(|(p, _)| p).collect();
+        assert_eq!(positions[5], (1, 1));
+        assert_eq!(positions.len(), 12);
+    }
+
+    #[test]
+    fn views() {
+        let mut mat = AoCMatrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6], vec![3, 2, 1]]);
+
+        assert_eq!(
+            mat.col(1).iter().copied().collect::<Vec<_>>(),
+            vec![2, 5, 2]
+        );
+        assert_eq!(mat.col(2)[1], 6);
+        assert_eq!(mat.row(0).rev(), mat.row(2));
+        assert_eq!(mat.row(0).rev().rev(), mat.row(0));
+        assert_ne!(mat.row(0), mat.col(0));
+
+        let distinct: HashSet<_> = mat.rows().chain(mat.rows().map(|r| r.rev())).collect();
+        assert_eq!(distinct.len(), 4);
+
+        let mut col = mat.col_mut(0).rev();
+        col[0] = 9;
+        col.swap(1, 2);
+        assert_eq!(mat.cols_by_value()[0], vec![4, 1, 9]);
+
+        for mut r in mat.rows_mut() {
+            r.iter_mut().for_each(|v| *v *= 10);
+        }
+        assert_eq!(mat.rows_by_value()[1], vec![10, 50, 60]);
+    }
+}
@@ -0,0 +1,220 @@
+use super::AoCMatrix;
+
+/// One of the 8 ways to lay a matrix down: mirrored left to right or not, then turned clockwise
+///     by some number of quarter turns
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Orientation {
+    pub flipped: bool,
+    /// Clockwise quarter turns, 0 to 3
+    pub quarter_turns: u8,
+}
+
+impl Orientation {
+    pub const IDENTITY: Orientation = Orientation {
+        flipped: false,
+        quarter_turns: 0,
+    };
+
+    /// The dihedral group of the square: every rotation, then every rotation of the mirror image
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(false, 0),
+        Orientation::new(false, 1),
+        Orientation::new(false, 2),
+        Orientation::new(false, 3),
+        Orientation::new(true, 0),
+        Orientation::new(true, 1),
+        Orientation::new(true, 2),
+        Orientation::new(true, 3),
+    ];
+
+    pub const fn new(flipped: bool, quarter_turns: u8) -> Orientation {
+        return Orientation {
+            flipped,
+            quarter_turns: quarter_turns % 4,
+        };
+    }
+}
+
+impl<T> AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    /// Swap rows for columns, so (r, c) moves to (c, r)
+    pub fn transposed(&self) -> AoCMatrix<T> {
+        let data = self.cols().flat_map(|c| c.iter().cloned()).collect();
+        return AoCMatrix {
+            data,
+            rows: self.cols,
+            cols: self.rows,
+        };
+    }
+    /// A quarter turn clockwise, so the first column read upwards becomes the first row
+    pub fn rotated_cw(&self) -> AoCMatrix<T> {
+        let data = self.cols().flat_map(|c| c.rev().iter().cloned()).collect();
+        return AoCMatrix {
+            data,
+            rows: self.cols,
+            cols: self.rows,
+        };
+    }
+    /// A quarter turn anticlockwise, so the last column becomes the first row
+    pub fn rotated_ccw(&self) -> AoCMatrix<T> {
+        let data = self.cols().rev().flat_map(|c| c.iter().cloned()).collect();
+        return AoCMatrix {
+            data,
+            rows: self.cols,
+            cols: self.rows,
+        };
+    }
+    pub fn rotated_180(&self) -> AoCMatrix<T> {
+        let mut mat = self.clone();
+        mat.rotate_180();
+        return mat;
+    }
+    /// Mirrored left to right, reversing each row
+    pub fn flipped_horizontal(&self) -> AoCMatrix<T> {
+        let mut mat = self.clone();
+        mat.flip_horizontal();
+        return mat;
+    }
+    /// Mirrored top to bottom, reversing the order of the rows
+    pub fn flipped_vertical(&self) -> AoCMatrix<T> {
+        let mut mat = self.clone();
+        mat.flip_vertical();
+        return mat;
+    }
+
+    /// Square matrices are transposed by swapping cells, others have to be rebuilt
+    pub fn transpose(&mut self) {
+        if self.rows != self.cols {
+            *self = self.transposed();
+            return;
+        }
+        for r in 0..self.rows {
+            for c in (r + 1)..self.cols {
+                self.data.swap(r * self.cols + c, c * self.cols + r);
+            }
+        }
+    }
+    pub fn rotate_cw(&mut self) {
+        self.transpose();
+        self.flip_horizontal();
+    }
+    pub fn rotate_ccw(&mut self) {
+        self.transpose();
+        self.flip_vertical();
+    }
+    pub fn rotate_180(&mut self) {
+        self.data.reverse();
+    }
+    pub fn flip_horizontal(&mut self) {
+        if self.cols == 0 {
+            return;
+        }
+        self.data
+            .chunks_mut(self.cols)
+            .for_each(|row| row.reverse());
+    }
+    pub fn flip_vertical(&mut self) {
+        for r in 0..(self.rows / 2) {
+            let other = self.rows - 1 - r;
+            for c in 0..self.cols {
+                self.data.swap(r * self.cols + c, other * self.cols + c);
+            }
+        }
+    }
+
+    /// The matrix laid down in `o`: mirrored first if `o.flipped`, then turned clockwise
+    pub fn oriented(&self, o: Orientation) -> AoCMatrix<T> {
+        let mut mat = if o.flipped {
+            self.flipped_horizontal()
+        } else {
+            self.clone()
+        };
+        match o.quarter_turns % 4 {
+            1 => mat.rotate_cw(),
+            2 => mat.rotate_180(),
+            3 => mat.rotate_ccw(),
+            _ => (),
+        }
+        return mat;
+    }
+    /// All 8 orientations, in the order of `Orientation::ALL`. Symmetric matrices repeat.
+    pub fn orientations(&self) -> impl Iterator<Item = (Orientation, AoCMatrix<T>)> + '_ {
+        return Orientation::ALL
+            .into_iter()
+            .map(move |o| (o, self.oriented(o)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn example() -> AoCMatrix<u32> {
+        return AoCMatrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn transforms() {
+        let mat = example();
+
+        assert_eq!(
+            mat.transposed().rows_by_value(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(
+            mat.rotated_cw().rows_by_value(),
+            vec![vec![4, 1], vec![5, 2], vec![6, 3]]
+        );
+        assert_eq!(
+            mat.rotated_ccw().rows_by_value(),
+            vec![vec![3, 6], vec![2, 5], vec![1, 4]]
+        );
+        assert_eq!(
+            mat.rotated_180().rows_by_value(),
+            vec![vec![6, 5, 4], vec![3, 2, 1]]
+        );
+        assert_eq!(
+            mat.flipped_horizontal().rows_by_value(),
+            vec![vec![3, 2, 1], vec![6, 5, 4]]
+        );
+        assert_eq!(
+            mat.flipped_vertical().rows_by_value(),
+            vec![vec![4, 5, 6], vec![1, 2, 3]]
+        );
+
+        let mut in_place = mat.clone();
+        in_place.rotate_cw();
+        assert_eq!(in_place, mat.rotated_cw());
+        in_place.rotate_ccw();
+        assert_eq!(in_place, mat);
+
+        let mut square = AoCMatrix::from_rows(vec![vec![1, 2], vec![3, 4]]);
+        square.transpose();
+        assert_eq!(square.rows_by_value(), vec![vec![1, 3], vec![2, 4]]);
+    }
+
+    #[test]
+    fn dihedral_group() {
+        let mat = example();
+        let all: HashSet<Vec<Vec<u32>>> =
+            mat.orientations().map(|(_, m)| m.rows_by_value()).collect();
+        assert_eq!(all.len(), 8);
+
+        // Flipping both ways is the same as half a turn
+        assert_eq!(
+            mat.oriented(Orientation::new(true, 2)),
+            mat.flipped_vertical()
+        );
+        assert_eq!(mat.oriented(Orientation::IDENTITY), mat);
+
+        let symmetric = AoCMatrix::filled_matrix('#', 2, 2);
+        let all: HashSet<Vec<Vec<char>>> = symmetric
+            .orientations()
+            .map(|(_, m)| m.rows_by_value())
+            .collect();
+        assert_eq!(all.len(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{Rev, StepBy, Take},
+    ops::{Index, IndexMut},
+    slice,
+};
+
+use itertools::Either;
+
+/// A row or column of a matrix, borrowed without copying. `data` starts at the first cell of the
+///     line and the cells are `stride` apart, so rows have a stride of 1 and columns a stride of
+///     the matrix's width. A reversed view walks the same cells from the far end.
+pub struct LineView<'a, T> {
+    data: &'a [T],
+    stride: usize,
+    len: usize,
+    reversed: bool,
+}
+
+/// The mutable counterpart of `LineView`
+pub struct LineViewMut<'a, T> {
+    data: &'a mut [T],
+    stride: usize,
+    len: usize,
+    reversed: bool,
+}
+
+pub type RowView<'a, T> = LineView<'a, T>;
+pub type ColView<'a, T> = LineView<'a, T>;
+pub type RowViewMut<'a, T> = LineViewMut<'a, T>;
+pub type ColViewMut<'a, T> = LineViewMut<'a, T>;
+
+type Strided<I> = Take<StepBy<I>>;
+pub type LineIter<'a, T> = Either<Rev<Strided<slice::Iter<'a, T>>>, Strided<slice::Iter<'a, T>>>;
+
+/// Position in `data` of the `idx`th cell of a line
+fn data_index(idx: usize, stride: usize, len: usize, reversed: bool) -> usize {
+    let idx = if reversed { len - 1 - idx } else { idx };
+    return idx * stride;
+}
+
+impl<'a, T> LineView<'a, T> {
+    pub(super) fn new(data: &'a [T], start: usize, stride: usize, len: usize) -> LineView<'a, T> {
+        // An empty line may start past the end of an empty matrix
+        let data = if len == 0 { &data[..0] } else { &data[start..] };
+        return LineView {
+            data,
+            stride,
+            len,
+            reversed: false,
+        };
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<&'a T> {
+        if idx >= self.len {
+            return None;
+        }
+        return Some(&self.data[data_index(idx, self.stride, self.len, self.reversed)]);
+    }
+    /// The same cells in the opposite order
+    pub fn rev(self) -> LineView<'a, T> {
+        return LineView {
+            reversed: !self.reversed,
+            ..self
+        };
+    }
+    pub fn iter(&self) -> LineIter<'a, T> {
+        return self.into_iter();
+    }
+}
+
+impl<'a, T> LineViewMut<'a, T> {
+    pub(super) fn new(
+        data: &'a mut [T],
+        start: usize,
+        stride: usize,
+        len: usize,
+    ) -> LineViewMut<'a, T> {
+        let data = if len == 0 {
+            &mut data[..0]
+        } else {
+            &mut data[start..]
+        };
+        return LineViewMut {
+            data,
+            stride,
+            len,
+            reversed: false,
+        };
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        return self.as_view().get(idx);
+    }
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        if idx >= self.len {
+            return None;
+        }
+        return Some(&mut self.data[data_index(idx, self.stride, self.len, self.reversed)]);
+    }
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let a = data_index(a, self.stride, self.len, self.reversed);
+        let b = data_index(b, self.stride, self.len, self.reversed);
+        self.data.swap(a, b);
+    }
+    /// The same cells in the opposite order
+    pub fn rev(self) -> LineViewMut<'a, T> {
+        return LineViewMut {
+            reversed: !self.reversed,
+            ..self
+        };
+    }
+    pub fn as_view(&self) -> LineView<'_, T> {
+        return LineView {
+            data: self.data,
+            stride: self.stride,
+            len: self.len,
+            reversed: self.reversed,
+        };
+    }
+    pub fn iter(&self) -> LineIter<'_, T> {
+        return self.as_view().iter();
+    }
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        let it = self.data.iter_mut().step_by(self.stride).take(self.len);
+        return if self.reversed {
+            Either::Left(it.rev())
+        } else {
+            Either::Right(it)
+        };
+    }
+}
+
+// Derived Clone and Copy would need `T: Copy`, but a view only copies its reference
+impl<'a, T> Clone for LineView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for LineView<'a, T> {}
+
+impl<'a, T> Index<usize> for LineView<'a, T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        return self.get(idx).expect("index out of range of line");
+    }
+}
+
+impl<'a, T> Index<usize> for LineViewMut<'a, T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        return self.get(idx).expect("index out of range of line");
+    }
+}
+
+impl<'a, T> IndexMut<usize> for LineViewMut<'a, T> {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        return self.get_mut(idx).expect("index out of range of line");
+    }
+}
+
+impl<'a, T> IntoIterator for LineView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = LineIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let it = self.data.iter().step_by(self.stride).take(self.len);
+        return if self.reversed {
+            Either::Left(it.rev())
+        } else {
+            Either::Right(it)
+        };
+    }
+}
+
+/// Views are equal when they hold equal cells in the same order, wherever they are in the matrix
+impl<'a, 'b, T: PartialEq> PartialEq<LineView<'b, T>> for LineView<'a, T> {
+    fn eq(&self, other: &LineView<'b, T>) -> bool {
+        return self.len == other.len && self.iter().eq(other.iter());
+    }
+}
+
+impl<'a, T: Eq> Eq for LineView<'a, T> {}
+
+impl<'a, T: Hash> Hash for LineView<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for v in self.iter() {
+            v.hash(state);
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for LineView<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_list().entries(self.iter()).finish();
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for LineViewMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return self.as_view().fmt(f);
+    }
+}
@@ -0,0 +1,196 @@
+use core::fmt;
+use std::{fmt::Display, ops::Index};
+
+use itertools::Itertools;
+
+use super::{AoCMatrix, LineView, RowView};
+
+/// A rectangular part of a matrix, borrowed without copying. Positions are relative to the
+///     window's top left corner, which is `origin` in the underlying matrix.
+pub struct Window<'a, T>
+where
+    T: std::clone::Clone,
+{
+    mat: &'a AoCMatrix<T>,
+    origin: (usize, usize),
+    rows: usize,
+    cols: usize,
+}
+
+impl<'a, T> Window<'a, T>
+where
+    T: std::clone::Clone,
+{
+    pub fn n_rows(&self) -> usize {
+        self.rows
+    }
+    pub fn n_cols(&self) -> usize {
+        self.cols
+    }
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+    pub fn in_window(&self, pos: (usize, usize)) -> bool {
+        return pos.0 < self.rows && pos.1 < self.cols;
+    }
+    pub fn get(&self, pos: (usize, usize)) -> Option<&'a T> {
+        if !self.in_window(pos) {
+            return None;
+        }
+        return Some(&self.mat[(self.origin.0 + pos.0, self.origin.1 + pos.1)]);
+    }
+
+    pub fn row(&self, r: usize) -> RowView<'a, T> {
+        assert!(r < self.rows, "row {} out of range", r);
+        let start = (self.origin.0 + r) * self.mat.cols + self.origin.1;
+        return LineView::new(&self.mat.data, start, 1, self.cols);
+    }
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = RowView<'a, T>> + ExactSizeIterator + '_ {
+        return (0..self.rows).map(|r| self.row(r));
+    }
+
+    /// Copy the window out into its own matrix
+    pub fn to_matrix(&self) -> AoCMatrix<T> {
+        return AoCMatrix::from_fn(self.rows, self.cols, |pos| self[pos].clone());
+    }
+}
+
+impl<T> AoCMatrix<T>
+where
+    T: std::clone::Clone,
+{
+    /// The cells from `top_left` up to but not including `bottom_right`, like a range. Panics if
+    ///     the window doesn't fit inside the matrix.
+    pub fn window(&self, top_left: (usize, usize), bottom_right: (usize, usize)) -> Window<'_, T> {
+        assert!(
+            top_left.0 <= bottom_right.0 && top_left.1 <= bottom_right.1,
+            "window corners {:?} and {:?} are the wrong way round",
+            top_left,
+            bottom_right
+        );
+        assert!(
+            bottom_right.0 <= self.rows && bottom_right.1 <= self.cols,
+            "window to {:?} is outside a {}x{} matrix",
+            bottom_right,
+            self.rows,
+            self.cols
+        );
+        return Window {
+            mat: self,
+            origin: top_left,
+            rows: bottom_right.0 - top_left.0,
+            cols: bottom_right.1 - top_left.1,
+        };
+    }
+    /// The whole matrix as a window, e.g. to `blit` all of it
+    pub fn as_window(&self) -> Window<'_, T> {
+        return self.window((0, 0), (self.rows, self.cols));
+    }
+
+    /// Copy `src` over the cells starting at `at`. Panics if it doesn't fit.
+    pub fn blit(&mut self, at: (usize, usize), src: &Window<T>) {
+        assert!(
+            at.0 + src.rows <= self.rows && at.1 + src.cols <= self.cols,
+            "a {}x{} window at {:?} is outside a {}x{} matrix",
+            src.rows,
+            src.cols,
+            at,
+            self.rows,
+            self.cols
+        );
+        for (r, row) in src.rows().enumerate() {
+            let start = (at.0 + r) * self.cols + at.1;
+            self.data[start..(start + src.cols)]
+                .iter_mut()
+                .zip(row)
+                .for_each(|(dst, v)| *dst = v.clone());
+        }
+    }
+
+    /// The matrix repeated `n_rows` times downwards and `n_cols` times across
+    pub fn tile(&self, n_rows: usize, n_cols: usize) -> AoCMatrix<T> {
+        return AoCMatrix::from_fn(self.rows * n_rows, self.cols * n_cols, |(r, c)| {
+            self[(r % self.rows, c % self.cols)].clone()
+        });
+    }
+
+    /// Index into the matrix as if it repeated forever in every direction
+    pub fn get_wrapped(&self, pos: (i64, i64)) -> &T {
+        return &self[self.wrap_position(pos)];
+    }
+    /// Map a position on the infinitely repeated matrix back to the original
+    pub fn wrap_position(&self, pos: (i64, i64)) -> (usize, usize) {
+        let row = pos.0.rem_euclid(self.rows as i64) as usize;
+        let col = pos.1.rem_euclid(self.cols as i64) as usize;
+        return (row, col);
+    }
+}
+
+impl<'a, T> Index<(usize, usize)> for Window<'a, T>
+where
+    T: std::clone::Clone,
+{
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        return self.get(pos).expect("position outside of window");
+    }
+}
+
+impl<'a, T: Clone + Display> Display for Window<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.rows()
+                .map(|r| r.iter().map(|c| c.to_string()).join(""))
+                .join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> AoCMatrix<char> {
+        return AoCMatrix::from_rows(vec![
+            "abcd".chars().collect(),
+            "efgh".chars().collect(),
+            "ijkl".chars().collect(),
+        ]);
+    }
+
+    #[test]
+    fn window_and_blit() {
+        let mat = example();
+        let win = mat.window((1, 1), (3, 3));
+
+        assert_eq!((win.n_rows(), win.n_cols()), (2, 2));
+        assert_eq!(win[(0, 0)], 'f');
+        assert_eq!(win.get((2, 0)), None);
+        assert_eq!(win.to_string(), "fg\njk");
+        assert_eq!(win.to_matrix().to_string(), "fg\njk");
+
+        let mut canvas = AoCMatrix::filled_matrix('.', 3, 4);
+        canvas.blit((0, 2), &win);
+        assert_eq!(canvas.to_string(), "..fg\n..jk\n....");
+
+        canvas.blit((0, 0), &mat.as_window());
+        assert_eq!(canvas, mat);
+    }
+
+    #[test]
+    fn tiling() {
+        let mat = AoCMatrix::from_rows(vec![vec!['a', 'b'], vec!['c', 'd']]);
+        let tiled = mat.tile(2, 3);
+
+        assert_eq!(tiled.to_string(), "ababab\ncdcdcd\nababab\ncdcdcd");
+        assert_eq!(*mat.get_wrapped((-1, -1)), 'd');
+        assert_eq!(*mat.get_wrapped((4, 5)), 'b');
+        assert_eq!(mat.wrap_position((-3, 2)), (1, 0));
+        for ((r, c), v) in tiled.indexed_iter() {
+            assert_eq!(mat.get_wrapped((r as i64, c as i64)), v);
+        }
+    }
+}
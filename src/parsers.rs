@@ -1,11 +1,24 @@
+//! Small nom parsers for the shapes puzzle lines come in, and helpers turning their failures into
+//! `ParseError`s. The combinators keep nom's plain `Error`, which only records where they gave
+//! up. The message comes from the caller instead, through `parse_with` or
+//! `ParseError::from_nom`, since only the day knows what it expected there. The error points at
+//! the line and column where parsing stopped.
+
+use std::str::FromStr;
+
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag},
-    multi::many0,
-    sequence::terminated,
+    bytes::complete::{is_not, tag, take_till1},
+    character::complete::{alphanumeric1, char, digit1, one_of, space0, space1},
+    combinator::{all_consuming, map_res, opt, recognize, rest},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, separated_pair, terminated, tuple},
     IResult,
 };
 
+use crate::common::ParseError;
+use crate::matrix::AoCMatrix;
+
 pub fn comma_separated(s: &str) -> IResult<&str, Vec<&str>> {
     let (rem, parsed) = many0(alt((terminated(is_not(","), tag(",")), is_not(","))))(s)?;
 
@@ -18,6 +31,131 @@ pub fn space_separated(s: &str) -> IResult<&str, Vec<&str>> {
     return Ok((rem, parsed));
 }
 
+/// An unsigned integer, which fails rather than wrapping if it doesn't fit in T
+pub fn unsigned<T: FromStr>(s: &str) -> IResult<&str, T> {
+    return map_res(digit1, str::parse)(s);
+}
+
+/// An integer with an optional leading `+` or `-`
+pub fn signed<T: FromStr>(s: &str) -> IResult<&str, T> {
+    return map_res(recognize(pair(opt(one_of("+-")), digit1)), str::parse)(s);
+}
+
+/// One or more unsigned integers separated by spaces or tabs, ignoring any either side
+pub fn unsigned_list<T: FromStr>(s: &str) -> IResult<&str, Vec<T>> {
+    return delimited(space0, separated_list1(space1, unsigned), space0)(s);
+}
+
+/// One or more signed integers separated by spaces or tabs, ignoring any either side
+pub fn signed_list<T: FromStr>(s: &str) -> IResult<&str, Vec<T>> {
+    return delimited(space0, separated_list1(space1, signed), space0)(s);
+}
+
+/// A `key: value` line, split at the first colon. The value is the rest of the line, without the
+///     whitespace after the colon.
+pub fn key_value(s: &str) -> IResult<&str, (&str, &str)> {
+    return separated_pair(take_till1(|c| c == ':'), pair(char(':'), space0), rest)(s);
+}
+
+/// The `key:` header of a line with this particular key, leaving whatever follows it
+pub fn header<'a>(key: &'a str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    return terminated(tag(key), pair(char(':'), space0));
+}
+
+/// A `label = (a, b)` line, as (label, (a, b))
+pub fn labelled_pair(s: &str) -> IResult<&str, (&str, (&str, &str))> {
+    let (rem, (label, _, pair)) = tuple((
+        alphanumeric1,
+        tag(" = "),
+        delimited(
+            char('('),
+            separated_pair(alphanumeric1, tag(", "), alphanumeric1),
+            char(')'),
+        ),
+    ))(s)?;
+
+    return Ok((rem, (label, pair)));
+}
+
+/// Run `parser` over the whole of `line`, the `idx`th line of the input. Anything it can't parse,
+///     including leftovers at the end, is an error pointing at where it stopped with the message
+///     `expected`.
+pub fn parse_with<'a, O, P>(
+    day: u32,
+    idx: usize,
+    line: &'a str,
+    parser: P,
+    expected: &str,
+) -> Result<O, ParseError>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    let (_, parsed) = all_consuming(parser)(line)
+        .map_err(|e| ParseError::from_nom(day, idx, line, e, expected))?;
+    return Ok(parsed);
+}
+
+/// Types which are read from a single character, such as the squares of a grid
+pub trait FromChar: Sized {
+    fn from_char(c: char) -> Option<Self>;
+}
+
+/// `parse_grid`, converting each character with `T::from_char`
+pub fn parse_char_grid<S, T>(
+    day: u32,
+    first_line: usize,
+    lines: &[S],
+) -> Result<AoCMatrix<T>, ParseError>
+where
+    S: AsRef<str>,
+    T: FromChar + Clone,
+{
+    return parse_grid(day, first_line, lines, T::from_char);
+}
+
+/// Parse lines of characters into a matrix, converting each character with `f`. Every line must be
+///     the same length. `first_line` is the index of `lines[0]` within the whole input, so that
+///     errors point at the right line.
+pub fn parse_grid<S, T, F>(
+    day: u32,
+    first_line: usize,
+    lines: &[S],
+    f: F,
+) -> Result<AoCMatrix<T>, ParseError>
+where
+    S: AsRef<str>,
+    T: Clone,
+    F: Fn(char) -> Option<T>,
+{
+    let width = match lines.first() {
+        Some(l) if !l.as_ref().is_empty() => l.as_ref().chars().count(),
+        _ => return Err(ParseError::new(day, first_line, 0, "", "expected a grid")),
+    };
+
+    let mut rows = vec![];
+    for (idx, l) in lines.iter().enumerate() {
+        let l = l.as_ref();
+        let mut row = Vec::with_capacity(width);
+        for (col, c) in l.chars().enumerate() {
+            match f(c) {
+                Some(v) => row.push(v),
+                None => {
+                    let msg = format!("unrecognized character '{}'", c);
+                    return Err(ParseError::new(day, first_line + idx, col, l, msg));
+                }
+            }
+        }
+        if row.len() != width {
+            let msg = format!("expected {} columns, found {}", width, row.len());
+            let col = std::cmp::min(row.len(), width);
+            return Err(ParseError::new(day, first_line + idx, col, l, msg));
+        }
+        rows.push(row);
+    }
+
+    return Ok(AoCMatrix::from_rows(rows));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +182,58 @@ mod tests {
         assert_eq!(result[1], "92".to_string());
         assert_eq!(result[2], "ASB".to_string());
     }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            unsigned_list::<u32>(" 41  48\t83 86 "),
+            Ok(("", vec![41, 48, 83, 86]))
+        );
+        assert_eq!(
+            signed_list::<i64>("0 -3 +6 -9"),
+            Ok(("", vec![0, -3, 6, -9]))
+        );
+        assert!(unsigned::<u8>("256").is_err());
+        assert!(unsigned_list::<u32>("").is_err());
+
+        assert_eq!(key_value("Time:  7 15"), Ok(("", ("Time", "7 15"))));
+        assert_eq!(header("seeds")("seeds: 79 14"), Ok(("79 14", "seeds")));
+        assert_eq!(
+            labelled_pair("AAA = (BBB, CCC)"),
+            Ok(("", ("AAA", ("BBB", "CCC"))))
+        );
+
+        let parsed = parse_with(0, 0, "1 2 3", unsigned_list::<u32>, "expected numbers");
+        assert_eq!(parsed, Ok(vec![1, 2, 3]));
+        let err = parse_with(0, 3, "1 2 x", unsigned_list::<u32>, "expected numbers").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 5));
+        assert_eq!(err.message, "expected numbers");
+    }
+
+    #[test]
+    fn test_grid() {
+        let to_digit = |c: char| c.to_digit(10);
+
+        let mat = parse_grid(0, 0, &["123", "456"], to_digit).unwrap();
+        assert_eq!(mat[(1, 2)], 6);
+
+        let err = parse_grid(0, 4, &["123", "4x6"], to_digit).unwrap_err();
+        assert_eq!((err.line, err.column), (6, 2));
+
+        let err = parse_grid(0, 0, &["123", "45"], to_digit).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        assert!(parse_grid(0, 0, &[] as &[&str], to_digit).is_err());
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Wall(bool);
+        impl FromChar for Wall {
+            fn from_char(c: char) -> Option<Wall> {
+                return matches!(c, '#' | '.').then(|| Wall(c == '#'));
+            }
+        }
+        let walls: AoCMatrix<Wall> = parse_char_grid(0, 0, &["#.", ".#"]).unwrap();
+        assert_eq!(walls[(1, 1)], Wall(true));
+        assert!(parse_char_grid::<_, Wall>(0, 0, &["#?"]).is_err());
+    }
 }
@@ -0,0 +1,260 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::runner::{Outcome, RunResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// human readable answers, or a table when running several parts
+    Text,
+    /// one JSON object per line, per result
+    Json,
+    /// tab separated values with a header row
+    Tsv,
+}
+
+/// Columns emitted by the structured formats, in order
+const FIELDS: [&str; 14] = [
+    "day",
+    "part",
+    "status",
+    "answer",
+    "answer_type",
+    "input_path",
+    "input_hash",
+    "split_ns",
+    "solve_ns",
+    "bench_runs",
+    "bench_min_ns",
+    "bench_median_ns",
+    "bench_max_ns",
+    "error",
+];
+
+/// A single field of a structured record
+enum Field {
+    Number(u128),
+    Text(String),
+    Null,
+}
+
+fn record(r: &RunResult) -> Vec<Field> {
+    let opt_text = |s: Option<String>| s.map_or(Field::Null, Field::Text);
+    let opt_number = |n: Option<u128>| n.map_or(Field::Null, Field::Number);
+
+    let (status, answer, answer_type, error) = match &r.outcome {
+        Outcome::Answer(a) => ("ok", Some(a.to_string()), Some(a.type_name()), None),
+        Outcome::MissingInput(p) => (
+            "missing_input",
+            None,
+            None,
+            Some(format!("missing input ({})", p.display())),
+        ),
+        Outcome::Invalid(e) => ("parse_error", None, None, Some(e.to_string())),
+        Outcome::Failed(e) => ("error", None, None, Some(e.clone())),
+    };
+
+    let input_path = r
+        .input
+        .as_ref()
+        .and_then(|i| i.path.as_ref())
+        .or(match &r.outcome {
+            Outcome::MissingInput(p) => Some(p),
+            _ => None,
+        })
+        .map(|p| p.display().to_string());
+
+    return vec![
+        Field::Number(r.day as u128),
+        Field::Number(r.part as u128),
+        Field::Text(status.to_string()),
+        opt_text(answer),
+        opt_text(answer_type.map(String::from)),
+        opt_text(input_path),
+        opt_text(r.input.as_ref().map(|i| i.hash.clone())),
+        opt_number(r.timing.and_then(|t| t.split).map(|d| d.as_nanos())),
+        opt_number(r.timing.map(|t| t.solve.as_nanos())),
+        opt_number(r.bench.map(|b| b.runs as u128)),
+        opt_number(r.bench.map(|b| b.min.as_nanos())),
+        opt_number(r.bench.map(|b| b.median.as_nanos())),
+        opt_number(r.bench.map(|b| b.max.as_nanos())),
+        opt_text(error),
+    ];
+}
+
+/// One JSON object per result, one result per line. Answers are emitted as strings, since
+///     u64 answers don't survive a round trip through a double
+pub fn format_json(results: &[RunResult]) -> String {
+    return results
+        .iter()
+        .map(|r| {
+            let entries: Vec<String> = FIELDS
+                .iter()
+                .zip(record(r))
+                .map(|(name, field)| {
+                    let value = match field {
+                        Field::Number(n) => n.to_string(),
+                        Field::Text(s) => json_string(&s),
+                        Field::Null => "null".to_string(),
+                    };
+                    format!("\"{}\":{}", name, value)
+                })
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+/// Tab separated values with a header row. Missing values are left empty
+pub fn format_tsv(results: &[RunResult]) -> String {
+    let mut rows = vec![FIELDS.join("\t")];
+
+    for r in results {
+        let values: Vec<String> = record(r)
+            .into_iter()
+            .map(|field| match field {
+                Field::Number(n) => n.to_string(),
+                Field::Text(s) => s.replace(['\t', '\n', '\r'], " "),
+                Field::Null => String::new(),
+            })
+            .collect();
+        rows.push(values.join("\t"));
+    }
+
+    return rows.join("\n");
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+/// Render results as a table, with split/solve timings when `show_time` is set and
+///     min/median/max columns for any benchmarked results
+pub fn format_table(results: &[RunResult], show_time: bool) -> String {
+    let show_bench = results.iter().any(|r| r.bench.is_some());
+
+    let mut header = format!("{:>3}  {:>4}  {:<20}", "Day", "Part", "Answer");
+    if show_time {
+        header += &format!("  {:>12}  {:>12}", "Split", "Solve");
+    }
+    if show_bench {
+        header += &format!("  {:>12}  {:>12}  {:>12}", "Min", "Median", "Max");
+    }
+    let mut rows = vec![header.trim_end().to_string()];
+
+    for r in results {
+        let answer = match &r.outcome {
+            Outcome::Answer(a) => a.to_string(),
+            Outcome::MissingInput(p) => format!("missing input ({})", p.display()),
+            Outcome::Invalid(e) => format!("parse error: {}", e),
+            Outcome::Failed(e) => format!("error: {}", e),
+        };
+        let mut row = format!("{:>3}  {:>4}  {:<20}", r.day, r.part, answer);
+        if show_time {
+            row += &match r.timing {
+                Some(t) => format!(
+                    "  {:>12}  {:>12}",
+                    t.split.map_or("-".to_string(), fmt_duration),
+                    fmt_duration(t.solve)
+                ),
+                None => format!("  {:>12}  {:>12}", "-", "-"),
+            };
+        }
+        if show_bench {
+            row += &match r.bench {
+                Some(b) => format!(
+                    "  {:>12}  {:>12}  {:>12}",
+                    fmt_duration(b.min),
+                    fmt_duration(b.median),
+                    fmt_duration(b.max)
+                ),
+                None => format!("  {:>12}  {:>12}  {:>12}", "-", "-", "-"),
+            };
+        }
+        rows.push(row.trim_end().to_string());
+    }
+
+    return rows.join("\n");
+}
+
+pub fn fmt_duration(d: Duration) -> String {
+    return format!("{:.3?}", d);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{InputInfo, Timing};
+    use crate::solver::Answer;
+    use std::path::PathBuf;
+
+    fn results() -> Vec<RunResult> {
+        return vec![
+            RunResult {
+                day: 1,
+                part: 1,
+                outcome: Outcome::Answer(Answer::U32(142)),
+                input: Some(InputInfo {
+                    path: Some(PathBuf::from("inputs/day01.txt")),
+                    hash: "00000000000000ff".to_string(),
+                }),
+                timing: Some(Timing {
+                    split: Some(Duration::from_nanos(10)),
+                    solve: Duration::from_nanos(20),
+                }),
+                bench: None,
+            },
+            RunResult {
+                day: 2,
+                part: 1,
+                outcome: Outcome::Failed("bad \"game\"".to_string()),
+                input: None,
+                timing: None,
+                bench: None,
+            },
+        ];
+    }
+
+    #[test]
+    fn json_records() {
+        let json = format_json(&results());
+        let lines: Vec<&str> = json.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "{\"day\":1,\"part\":1,\"status\":\"ok\",\"answer\":\"142\",\"answer_type\":\"u32\",\"input_path\":\"inputs/day01.txt\",\"input_hash\":\"00000000000000ff\",\"split_ns\":10,\"solve_ns\":20,\"bench_runs\":null,\"bench_min_ns\":null,\"bench_median_ns\":null,\"bench_max_ns\":null,\"error\":null}"
+        );
+        assert!(lines[1].contains("\"status\":\"error\""));
+        assert!(lines[1].contains("\"error\":\"bad \\\"game\\\"\""));
+    }
+
+    #[test]
+    fn tsv_records() {
+        let tsv = format_tsv(&results());
+        let lines: Vec<&str> = tsv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].split('\t').count(), FIELDS.len());
+        assert_eq!(
+            lines[1],
+            "1\t1\tok\t142\tu32\tinputs/day01.txt\t00000000000000ff\t10\t20\t\t\t\t\t"
+        );
+        assert!(lines[2].ends_with("\tbad \"game\""));
+    }
+}
@@ -0,0 +1,440 @@
+use std::io::{BufRead, ErrorKind};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::common::{
+    input_hash, read_input, HashingReader, InputError, InvalidDayRangeError, NotImplementedError,
+    ParseError,
+};
+use crate::solver::{registry, Answer, Solver, SolverResult};
+
+/// An inclusive span of days, parsed from `5`, `3..10` (exclusive end, as in Rust) or `3..=10`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayRange {
+    days: RangeInclusive<u32>,
+}
+
+impl DayRange {
+    pub fn new(first: u32, last: u32) -> DayRange {
+        return DayRange { days: first..=last };
+    }
+    pub fn all() -> DayRange {
+        return DayRange::new(1, 25);
+    }
+    pub fn contains(&self, day: u32) -> bool {
+        return self.days.contains(&day);
+    }
+}
+
+impl FromStr for DayRange {
+    type Err = InvalidDayRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |d: &str| -> Result<u32, InvalidDayRangeError> {
+            d.trim()
+                .parse()
+                .map_err(|_| InvalidDayRangeError(s.to_string()))
+        };
+
+        let range = if let Some((first, last)) = s.split_once("..=") {
+            DayRange::new(parse_day(first)?, parse_day(last)?)
+        } else if let Some((first, last)) = s.split_once("..") {
+            let last = parse_day(last)?;
+            if last == 0 {
+                return Err(InvalidDayRangeError(s.to_string()));
+            }
+            DayRange::new(parse_day(first)?, last - 1)
+        } else {
+            let day = parse_day(s)?;
+            DayRange::new(day, day)
+        };
+
+        if range.days.is_empty() {
+            return Err(InvalidDayRangeError(s.to_string()));
+        }
+        return Ok(range);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Answer(Answer),
+    MissingInput(PathBuf),
+    /// The input was malformed, kept whole so that it can be rendered as a diagnostic
+    Invalid(ParseError),
+    Failed(String),
+}
+
+/// Wall-clock time spent in each stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// Splitting the input up with `Solver::split`, before solving. None for streamed runs, which
+    ///     read a line at a time as they solve.
+    pub split: Option<Duration>,
+    pub solve: Duration,
+}
+
+/// Summary of repeated solves of the same part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchStats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl BenchStats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Option<BenchStats> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+
+        return Some(BenchStats {
+            runs: samples.len(),
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        });
+    }
+}
+
+/// Where an input came from (if it was a file) and a hash of its contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputInfo {
+    pub path: Option<PathBuf>,
+    pub hash: String,
+}
+
+impl InputInfo {
+    pub fn new(input: &str, path: Option<&Path>) -> InputInfo {
+        return InputInfo {
+            path: path.map(|p| p.to_path_buf()),
+            hash: input_hash(input),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    pub day: u32,
+    pub part: u32,
+    pub outcome: Outcome,
+    pub input: Option<InputInfo>,
+    pub timing: Option<Timing>,
+    pub bench: Option<BenchStats>,
+}
+
+impl RunResult {
+    pub fn succeeded(&self) -> bool {
+        return matches!(self.outcome, Outcome::Answer(_));
+    }
+}
+
+/// Location of the input for a given day under the `inputs/dayNN.txt` convention
+pub fn input_path(input_dir: &Path, day: u32) -> PathBuf {
+    return input_dir.join(format!("day{:02}.txt", day));
+}
+
+/// Split and solve a single part, timing each stage separately
+pub fn timed_solve(solver: &dyn Solver, input: &str, part: u32) -> (SolverResult, Timing) {
+    let start = Instant::now();
+    let lines = solver.split(input);
+    let split = Instant::now();
+    let result = if solver.has_part(part) {
+        solver.solve_split(&lines, part)
+    } else {
+        Err(NotImplementedError.into())
+    };
+    let solved = Instant::now();
+
+    let timing = Timing {
+        split: Some(split - start),
+        solve: solved - split,
+    };
+    return (result, timing);
+}
+
+/// Solve a single part `runs` times from the same split input. Only the solve itself is timed.
+pub fn bench_solve(
+    solver: &dyn Solver,
+    input: &str,
+    part: u32,
+    runs: usize,
+) -> Result<Option<BenchStats>, Box<dyn std::error::Error>> {
+    if !solver.has_part(part) {
+        return Err(NotImplementedError.into());
+    }
+    let lines = solver.split(input);
+    let mut samples = vec![];
+    for _ in 0..runs {
+        let start = Instant::now();
+        solver.solve_split(&lines, part)?;
+        samples.push(start.elapsed());
+    }
+
+    return Ok(BenchStats::from_samples(samples));
+}
+
+/// Run every implemented part of every implemented day in `days`, reading inputs from `input_dir`.
+///     When `part` is given, only that part is run for each day. When `bench` is given, each
+///     part is additionally solved that many times.
+pub fn run_days(
+    days: &DayRange,
+    part: Option<u32>,
+    input_dir: &Path,
+    bench: Option<usize>,
+) -> Vec<RunResult> {
+    let mut results = vec![];
+
+    for solver in registry().iter().filter(|s| days.contains(s.day())) {
+        let parts: Vec<u32> = solver
+            .parts()
+            .into_iter()
+            .filter(|p| part.map_or(true, |only| only == *p))
+            .collect();
+
+        let path = input_path(input_dir, solver.day());
+        let input = read_input(&path);
+
+        for p in parts {
+            let outcome = match &input {
+                Ok(s) => {
+                    results.push(run_input(solver.as_ref(), s, Some(&path), p, bench));
+                    continue;
+                }
+                Err(e) if e.source.kind() == ErrorKind::NotFound => {
+                    Outcome::MissingInput(path.clone())
+                }
+                Err(e) => Outcome::Failed(e.to_string()),
+            };
+            let result = RunResult {
+                day: solver.day(),
+                part: p,
+                outcome,
+                input: None,
+                timing: None,
+                bench: None,
+            };
+            results.push(result);
+        }
+    }
+
+    return results;
+}
+
+/// Run a single part on the given input, recording where it came from along with timings
+pub fn run_input(
+    solver: &dyn Solver,
+    input: &str,
+    path: Option<&Path>,
+    part: u32,
+    bench: Option<usize>,
+) -> RunResult {
+    let (result, timing) = timed_solve(solver, input, part);
+    let outcome = outcome_of(result);
+
+    // No point in benchmarking something which doesn't produce an answer
+    let bench = match (&outcome, bench) {
+        (Outcome::Answer(_), Some(runs)) => bench_solve(solver, input, part, runs).unwrap_or(None),
+        _ => None,
+    };
+
+    return RunResult {
+        day: solver.day(),
+        part,
+        outcome,
+        input: Some(InputInfo::new(input, path)),
+        timing: Some(timing),
+        bench,
+    };
+}
+
+/// Run each of `parts` on each file in turn. A file which can't be read fails its own parts,
+///     keeping the reason, rather than stopping the rest from running.
+pub fn run_files(
+    solver: &dyn Solver,
+    paths: &[PathBuf],
+    parts: &[u32],
+    bench: Option<usize>,
+) -> Vec<RunResult> {
+    let mut results = vec![];
+
+    for path in paths {
+        let input = read_input(path);
+        for part in parts {
+            results.push(match &input {
+                Ok(s) => run_input(solver, s, Some(path), *part, bench),
+                Err(e) => unreadable_input(solver.day(), *part, e),
+            });
+        }
+    }
+
+    return results;
+}
+
+/// A part which couldn't be run because its input couldn't be read
+pub fn unreadable_input(day: u32, part: u32, e: &InputError) -> RunResult {
+    return RunResult {
+        day,
+        part,
+        outcome: Outcome::Failed(e.to_string()),
+        input: None,
+        timing: None,
+        bench: None,
+    };
+}
+
+/// Run a single part, reading the input as the solver goes rather than all at once. Timing
+///     includes reading and parsing, since they can't be told apart from solving.
+pub fn run_stream(
+    solver: &dyn Solver,
+    reader: impl BufRead,
+    path: Option<&Path>,
+    part: u32,
+) -> RunResult {
+    let mut reader = HashingReader::new(reader);
+    let start = Instant::now();
+    let result = if solver.has_part(part) && solver.streams() {
+        solver.solve_stream(&mut reader, part)
+    } else {
+        Err(NotImplementedError.into())
+    };
+    let timing = Timing {
+        split: None,
+        solve: start.elapsed(),
+    };
+
+    return RunResult {
+        day: solver.day(),
+        part,
+        outcome: outcome_of(result),
+        input: Some(InputInfo {
+            path: path.map(|p| p.to_path_buf()),
+            hash: reader.hash(),
+        }),
+        timing: Some(timing),
+        bench: None,
+    };
+}
+
+fn outcome_of(result: SolverResult) -> Outcome {
+    return match result {
+        Ok(answer) => Outcome::Answer(answer),
+        Err(e) => match e.downcast::<ParseError>() {
+            Ok(parse_error) => Outcome::Invalid(*parse_error),
+            Err(e) => Outcome::Failed(e.to_string()),
+        },
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_day_ranges() {
+        assert_eq!("5".parse::<DayRange>().unwrap(), DayRange::new(5, 5));
+        assert_eq!("3..10".parse::<DayRange>().unwrap(), DayRange::new(3, 9));
+        assert_eq!("3..=10".parse::<DayRange>().unwrap(), DayRange::new(3, 10));
+
+        assert!("10..3".parse::<DayRange>().is_err());
+        assert!("3..3".parse::<DayRange>().is_err());
+        assert!("three".parse::<DayRange>().is_err());
+    }
+
+    #[test]
+    fn missing_inputs_are_reported() {
+        let dir = Path::new("definitely/not/a/real/dir");
+        let results = run_days(&DayRange::new(1, 2), None, dir, None);
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].outcome,
+            Outcome::MissingInput(dir.join("day01.txt"))
+        );
+        assert!(results.iter().all(|r| !r.succeeded()));
+
+        let results = run_days(&DayRange::new(1, 2), Some(2), dir, None);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.part == 2));
+    }
+
+    #[test]
+    fn answers_from_input_dir() {
+        let dir = std::env::temp_dir().join("aoc23_runner_answers_from_input_dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            input_path(&dir, 1),
+            "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet",
+        )
+        .unwrap();
+
+        let results = run_days(&DayRange::new(1, 1), Some(1), &dir, Some(3));
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].outcome, Outcome::Answer(Answer::U32(142)));
+        assert_eq!(
+            results[0].input.as_ref().unwrap().path,
+            Some(input_path(&dir, 1))
+        );
+        assert!(results[0].timing.unwrap().split.is_some());
+        assert_eq!(results[0].bench.unwrap().runs, 3);
+    }
+
+    #[test]
+    fn unreadable_files_fail_alone() {
+        let dir = std::env::temp_dir().join("aoc23_runner_unreadable_files_fail_alone");
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.txt");
+        std::fs::write(&good, "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet").unwrap();
+        let missing = dir.join("missing.txt");
+        let day1 = crate::solver::find_solver(1).unwrap();
+
+        let results = run_files(day1.as_ref(), &[missing.clone(), good], &[1, 2], None);
+
+        assert_eq!(results.len(), 4);
+        for r in &results[..2] {
+            match &r.outcome {
+                Outcome::Failed(e) => {
+                    assert!(e.starts_with(&format!("Unable to read {}: ", missing.display())))
+                }
+                other => panic!("expected a read failure, got {:?}", other),
+            }
+        }
+        assert_eq!(results[2].outcome, Outcome::Answer(Answer::U32(142)));
+        assert_eq!(results[3].outcome, Outcome::Answer(Answer::U32(142)));
+    }
+
+    #[test]
+    fn streamed_inputs_match_whole_ones() {
+        let input = "1abc2\r\npqr3stu8vwx\r\na1b2c3d4e5f\r\ntreb7uchet\r\n";
+        let day1 = crate::solver::find_solver(1).unwrap();
+
+        let whole = run_input(day1.as_ref(), input, None, 1, None);
+        let streamed = run_stream(day1.as_ref(), input.as_bytes(), None, 1);
+
+        assert_eq!(streamed.outcome, whole.outcome);
+        assert_eq!(streamed.input.unwrap().hash, whole.input.unwrap().hash);
+        assert_eq!(streamed.timing.unwrap().split, None);
+
+        let day3 = crate::solver::find_solver(3).unwrap();
+        let streamed = run_stream(day3.as_ref(), "467..114..".as_bytes(), None, 1);
+        assert!(matches!(streamed.outcome, Outcome::Failed(_)));
+    }
+
+    #[test]
+    fn bench_stats() {
+        let samples = [5, 1, 3, 2, 4].map(Duration::from_millis).to_vec();
+        let stats = BenchStats::from_samples(samples).unwrap();
+
+        assert_eq!(stats.runs, 5);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.max, Duration::from_millis(5));
+
+        assert_eq!(BenchStats::from_samples(vec![]), None);
+    }
+}
@@ -0,0 +1,357 @@
+#![allow(dead_code)]
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+use num::Zero;
+
+use crate::matrix::AoCMatrix;
+
+type POSITION = (usize, usize);
+
+/// Everything a search reached: the best known distance to each state and the state it was
+///     reached from, so paths can be walked back to a start. Searches given a goal stop as soon as
+///     it's settled, so `goal` is None if no goal was reachable.
+#[derive(Clone, Debug)]
+pub struct Search<S, C> {
+    dist: HashMap<S, C>,
+    prev: HashMap<S, S>,
+    goal: Option<S>,
+}
+
+impl<S, C> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy,
+{
+    fn new() -> Search<S, C> {
+        return Search {
+            dist: HashMap::new(),
+            prev: HashMap::new(),
+            goal: None,
+        };
+    }
+
+    pub fn distance(&self, s: &S) -> Option<C> {
+        return self.dist.get(s).copied();
+    }
+    pub fn reached(&self, s: &S) -> bool {
+        return self.dist.contains_key(s);
+    }
+    /// Every reached state with its distance, in no particular order
+    pub fn reached_states(&self) -> impl Iterator<Item = (&S, C)> {
+        return self.dist.iter().map(|(s, d)| (s, *d));
+    }
+    pub fn len(&self) -> usize {
+        self.dist.len()
+    }
+    /// The state `s` was reached from, None for starts and unreached states
+    pub fn predecessor(&self, s: &S) -> Option<&S> {
+        return self.prev.get(s);
+    }
+    pub fn predecessors(&self) -> &HashMap<S, S> {
+        &self.prev
+    }
+
+    /// The states from a start up to and including `s`
+    pub fn path_to(&self, s: &S) -> Option<Vec<S>> {
+        if !self.reached(s) {
+            return None;
+        }
+        let mut path = vec![s.clone()];
+        while let Some(p) = self.prev.get(path.last().unwrap()) {
+            path.push(p.clone());
+        }
+        path.reverse();
+        return Some(path);
+    }
+
+    pub fn goal(&self) -> Option<&S> {
+        return self.goal.as_ref();
+    }
+    pub fn goal_distance(&self) -> Option<C> {
+        return self.distance(self.goal.as_ref()?);
+    }
+    pub fn goal_path(&self) -> Option<Vec<S>> {
+        return self.path_to(self.goal.as_ref()?);
+    }
+}
+
+/*
+    Searches over any state type
+*/
+
+/// Breadth first search from `starts` until every reachable state has been found
+pub fn bfs<S, F, I>(starts: impl IntoIterator<Item = S>, successors: F) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    return bfs_to(starts, successors, |_| false);
+}
+
+/// Breadth first search from `starts`, stopping at the first state where `is_goal` holds
+pub fn bfs_to<S, F, I, G>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: F,
+    mut is_goal: G,
+) -> Search<S, usize>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for s in starts {
+        if !search.reached(&s) {
+            search.dist.insert(s.clone(), 0);
+            queue.push_back(s);
+        }
+    }
+
+    while let Some(s) = queue.pop_front() {
+        if is_goal(&s) {
+            search.goal = Some(s);
+            break;
+        }
+        let next_dist = search.dist[&s] + 1;
+        for n in successors(&s) {
+            if !search.reached(&n) {
+                search.dist.insert(n.clone(), next_dist);
+                search.prev.insert(n.clone(), s.clone());
+                queue.push_back(n);
+            }
+        }
+    }
+
+    return search;
+}
+
+/// Cheapest paths from `starts` to the first state where `is_goal` holds. `successors` gives
+///     each next state with the cost of moving to it.
+pub fn dijkstra<S, C, F, I, G>(
+    starts: impl IntoIterator<Item = S>,
+    successors: F,
+    is_goal: G,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    return astar(starts, successors, |_| C::zero(), is_goal);
+}
+
+/// Dijkstra's search guided by `heuristic`, an estimate of the remaining cost to a goal. The
+///     result is only the cheapest if the heuristic never overestimates and never drops by more
+///     than the cost of a step.
+pub fn astar<S, C, F, I, H, G>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: F,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Search<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    let mut search = Search::new();
+    // States aren't necessarily Ord, so the heap holds (estimate, cost, index into `states`)
+    let mut states = vec![];
+    let mut heap = BinaryHeap::new();
+
+    for s in starts {
+        if !search.reached(&s) {
+            search.dist.insert(s.clone(), C::zero());
+            heap.push(Reverse((heuristic(&s), C::zero(), states.len())));
+            states.push(s);
+        }
+    }
+
+    while let Some(Reverse((_, cost, idx))) = heap.pop() {
+        let s = &states[idx];
+        // A cheaper route to this state was found after this entry was pushed
+        if cost > search.dist[s] {
+            continue;
+        }
+        if is_goal(s) {
+            search.goal = Some(s.clone());
+            break;
+        }
+
+        let s = s.clone();
+        for (n, step_cost) in successors(&s) {
+            let new_cost = cost + step_cost;
+            if search.distance(&n).map_or(true, |d| new_cost < d) {
+                search.dist.insert(n.clone(), new_cost);
+                search.prev.insert(n.clone(), s.clone());
+                heap.push(Reverse((new_cost + heuristic(&n), new_cost, states.len())));
+                states.push(n);
+            }
+        }
+    }
+
+    return search;
+}
+
+/*
+    Searches over the cells of a matrix
+*/
+
+/// Steps from `start` to every cell it can reach through 4-neighbours, moving only where
+///     `can_step(from, to)` holds. Unreachable cells are None.
+pub fn distance_map<T, F>(
+    mat: &AoCMatrix<T>,
+    start: POSITION,
+    mut can_step: F,
+) -> AoCMatrix<Option<usize>>
+where
+    T: Clone,
+    F: FnMut(POSITION, POSITION) -> bool,
+{
+    let search = bfs([start], |p| {
+        let p = *p;
+        mat.neighbours4(p)
+            .filter(|n| can_step(p, *n))
+            .collect::<Vec<_>>()
+    });
+
+    let mut dists = AoCMatrix::filled_matrix(None, mat.n_rows(), mat.n_cols());
+    for (p, d) in search.reached_states() {
+        dists[*p] = Some(d);
+    }
+    return dists;
+}
+
+/// The cells connected to `start` through 4-neighbours that are all `passable`, including
+///     `start` itself
+pub fn flood_fill<T, F>(mat: &AoCMatrix<T>, start: POSITION, mut passable: F) -> AoCMatrix<bool>
+where
+    T: Clone,
+    F: FnMut(&T) -> bool,
+{
+    let dists = distance_map(mat, start, |_, to| passable(&mat[to]));
+    return AoCMatrix::from_fn(mat.n_rows(), mat.n_cols(), |p| dists[p].is_some());
+}
+
+/// Label each cell with the index of its connected component, where 4-neighbours are connected
+///     if `same` holds for their values. Labels count up from 0 in row-major order of each
+///     component's first cell. Also returns the number of components.
+pub fn connected_components<T, F>(mat: &AoCMatrix<T>, mut same: F) -> (AoCMatrix<usize>, usize)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut labels: AoCMatrix<Option<usize>> =
+        AoCMatrix::filled_matrix(None, mat.n_rows(), mat.n_cols());
+    let mut n_components = 0;
+    // Shared by every fill, so each cell is only labelled and looked around once overall
+    let mut to_visit = vec![];
+
+    for r in 0..mat.n_rows() {
+        for c in 0..mat.n_cols() {
+            if labels[(r, c)].is_some() {
+                continue;
+            }
+            labels[(r, c)] = Some(n_components);
+            to_visit.push((r, c));
+            while let Some(p) = to_visit.pop() {
+                for n in mat.neighbours4(p) {
+                    if labels[n].is_none() && same(&mat[p], &mat[n]) {
+                        labels[n] = Some(n_components);
+                        to_visit.push(n);
+                    }
+                }
+            }
+            n_components += 1;
+        }
+    }
+
+    // Every cell is in the component started from it, if not an earlier one
+    let labels = AoCMatrix::from_fn(mat.n_rows(), mat.n_cols(), |p| labels[p].unwrap());
+    return (labels, n_components);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze() -> AoCMatrix<char> {
+        return AoCMatrix::from_rows(
+            ["..#.", ".##.", "....", "#.#."]
+                .iter()
+                .map(|l| l.chars().collect())
+                .collect(),
+        );
+    }
+
+    #[test]
+    fn weighted_searches() {
+        // Walk a number line where doubling is cheap but adding one is not
+        let successors = |n: &u32| vec![(n + 1, 3), (n * 2, 1)];
+
+        let search = dijkstra([1], successors, |n| *n == 12);
+        assert_eq!(search.goal_distance(), Some(6));
+        assert_eq!(search.goal_path(), Some(vec![1, 2, 3, 6, 12]));
+
+        let guided = astar(
+            [1],
+            successors,
+            |n| if *n > 12 { 100 } else { 0 },
+            |n| *n == 12,
+        );
+        assert_eq!(guided.goal_distance(), Some(6));
+        assert!(guided.len() <= search.len());
+
+        // Odd numbers only, up to 21
+        let nowhere = dijkstra([1], |n: &u32| (*n < 20).then(|| (n + 2, 1)), |n| *n == 12);
+        assert_eq!(nowhere.goal(), None);
+        assert_eq!(nowhere.goal_distance(), None);
+
+        let bounded = bfs([0], |n: &i32| {
+            [n - 1, n + 1].into_iter().filter(|v| v.abs() <= 3)
+        });
+        assert_eq!(bounded.len(), 7);
+        assert_eq!(bounded.distance(&-3), Some(3));
+        assert_eq!(bounded.path_to(&2), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn grid_searches() {
+        let mat = maze();
+        let open = |p: POSITION| mat[p] == '.';
+
+        let dists = distance_map(&mat, (0, 0), |_, to| open(to));
+        assert_eq!(dists[(0, 3)], Some(7));
+        assert_eq!(dists[(3, 1)], Some(4));
+        assert_eq!(dists[(0, 2)], None);
+
+        let filled = flood_fill(&mat, (0, 0), |c| *c == '.');
+        assert_eq!(filled.get_data().iter().filter(|b| **b).count(), 11);
+
+        let (labels, n) = connected_components(&mat, |a, b| a == b);
+        // The open cells, the wall in the middle, and the two corners of the bottom row
+        assert_eq!(n, 4);
+        assert_eq!(labels[(0, 0)], 0);
+        assert_eq!(labels[(1, 2)], labels[(0, 2)]);
+        assert_ne!(labels[(3, 0)], labels[(3, 2)]);
+
+        // A checkerboard is all single cell components
+        let board = AoCMatrix::from_fn(300, 300, |(r, c)| (r + c) % 2);
+        let (labels, n) = connected_components(&board, |a, b| a == b);
+        assert_eq!(n, 300 * 300);
+        assert_eq!(labels[(299, 299)], 300 * 300 - 1);
+    }
+}
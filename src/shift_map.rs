@@ -0,0 +1,285 @@
+use core::fmt;
+use std::fmt::Display;
+
+use itertools::Itertools;
+use num::PrimInt;
+
+use crate::half_interval::{HalfInterval, IntervalSet};
+
+/// A map from integers to integers made of pieces which each shift one interval onto another,
+///     with every value outside the pieces mapping to itself. The pieces are kept sorted by
+///     source, so lookups are a binary search. Pieces which map onto themselves are dropped and
+///     neighbouring pieces with the same shift merged, so equal maps compare equal.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ShiftMap<T: PrimInt> {
+    /// (source, destination) pairs of the same length
+    pieces: Vec<(HalfInterval<T>, HalfInterval<T>)>,
+}
+
+/// `int` moved by the shift which takes `from` to `to`
+fn moved<T: PrimInt>(int: HalfInterval<T>, from: T, to: T) -> HalfInterval<T> {
+    let shifted = if to >= from {
+        int.shift_up(to - from)
+    } else {
+        int.shift_down(from - to)
+    };
+    return shifted.expect("shifted interval stays inside its piece");
+}
+
+impl<T: PrimInt> ShiftMap<T> {
+    /// The map taking every value to itself
+    pub fn identity() -> ShiftMap<T> {
+        return ShiftMap { pieces: vec![] };
+    }
+
+    /// A map sending each source interval to the one starting at its destination. None if the
+    ///     sources overlap, or a destination runs past the largest T.
+    pub fn from_pieces(
+        pieces: impl IntoIterator<Item = (HalfInterval<T>, T)>,
+    ) -> Option<ShiftMap<T>> {
+        let pieces = pieces
+            .into_iter()
+            .map(|(source, dest)| Some((source, HalfInterval::checked_new(dest, source.delta())?)))
+            .collect::<Option<Vec<_>>>()?;
+        return ShiftMap::normalised(pieces);
+    }
+
+    fn normalised(mut pieces: Vec<(HalfInterval<T>, HalfInterval<T>)>) -> Option<ShiftMap<T>> {
+        pieces.retain(|(source, dest)| !source.is_empty() && source != dest);
+        pieces.sort_by_key(|(source, _)| source.lb());
+
+        let mut merged: Vec<(HalfInterval<T>, HalfInterval<T>)> = vec![];
+        for (source, dest) in pieces {
+            match merged.last_mut() {
+                Some((last_source, _)) if source.lb() < last_source.ub() => return None,
+                Some((last_source, last_dest))
+                    if source.lb() == last_source.ub() && dest.lb() == last_dest.ub() =>
+                {
+                    *last_source = HalfInterval::from_bounds(last_source.lb(), source.ub());
+                    *last_dest = HalfInterval::from_bounds(last_dest.lb(), dest.ub());
+                }
+                _ => merged.push((source, dest)),
+            }
+        }
+        return Some(ShiftMap { pieces: merged });
+    }
+
+    /// The (source, destination) pairs, sorted by source
+    pub fn pieces(&self) -> impl Iterator<Item = &(HalfInterval<T>, HalfInterval<T>)> {
+        return self.pieces.iter();
+    }
+    pub fn is_identity(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    pub fn get(&self, v: T) -> T {
+        // The only piece which could hold v is the last one starting at or before it
+        let idx = self.pieces.partition_point(|(source, _)| source.lb() <= v);
+        return match idx.checked_sub(1).map(|i| self.pieces[i]) {
+            Some((source, dest)) if source.contains(v) => dest.lb() + (v - source.lb()),
+            _ => v,
+        };
+    }
+
+    /// `int` cut up where it crosses pieces, each part with where it ends up. The parts between
+    ///     pieces map to themselves.
+    fn segments(&self, int: HalfInterval<T>) -> Vec<(HalfInterval<T>, HalfInterval<T>)> {
+        let mut segments = vec![];
+        let mut lb = int.lb();
+
+        let first = self
+            .pieces
+            .partition_point(|(source, _)| source.ub() <= int.lb());
+        for (source, dest) in self.pieces[first..]
+            .iter()
+            .take_while(|(source, _)| source.lb() < int.ub())
+        {
+            if lb < source.lb() {
+                let gap = HalfInterval::from_bounds(lb, source.lb());
+                segments.push((gap, gap));
+            }
+            if let Some(part) = source.intersect(&int) {
+                segments.push((part, moved(part, source.lb(), dest.lb())));
+                lb = part.ub();
+            }
+        }
+        if lb < int.ub() {
+            let gap = HalfInterval::from_bounds(lb, int.ub());
+            segments.push((gap, gap));
+        }
+        return segments;
+    }
+
+    /// Where every value in `int` ends up
+    pub fn map_interval(&self, int: HalfInterval<T>) -> IntervalSet<T> {
+        return self
+            .segments(int)
+            .into_iter()
+            .map(|(_, dest)| dest)
+            .collect();
+    }
+    pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        return set
+            .iter()
+            .flat_map(|int| self.segments(*int))
+            .map(|(_, dest)| dest)
+            .collect();
+    }
+
+    /// Every value which ends up in `set`
+    pub fn preimage(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let sources: IntervalSet<T> = self.pieces.iter().map(|(source, _)| *source).collect();
+        let unmoved = set.difference(&sources);
+
+        let moved_back = self.pieces.iter().flat_map(|(source, dest)| {
+            set.intersection(&(*dest).into())
+                .iter()
+                .map(|int| moved(*int, dest.lb(), source.lb()))
+                .collect::<Vec<_>>()
+        });
+        return moved_back.chain(unmoved.iter().copied()).collect();
+    }
+
+    /// The map applying `self` and then `next`
+    pub fn then(&self, next: &ShiftMap<T>) -> ShiftMap<T> {
+        // Pieces of `self` are cut wherever their destinations cross pieces of `next`, and the
+        //     values `self` leaves alone can still be moved by `next`
+        let everything = HalfInterval::from_bounds(T::min_value(), T::max_value());
+        let pieces = self
+            .segments(everything)
+            .into_iter()
+            .flat_map(|(source, dest)| {
+                next.segments(dest)
+                    .into_iter()
+                    .map(move |(mid, end)| (moved(mid, dest.lb(), source.lb()), end))
+            })
+            .collect();
+        return ShiftMap::normalised(pieces).expect("pieces of a composition can't overlap");
+    }
+
+    /// The map undoing this one, if every value comes from exactly one other. That's when the
+    ///     pieces' destinations cover exactly the same values as their sources.
+    pub fn inverse(&self) -> Option<ShiftMap<T>> {
+        let sources: IntervalSet<T> = self.pieces.iter().map(|(source, _)| *source).collect();
+        let dests: IntervalSet<T> = self.pieces.iter().map(|(_, dest)| *dest).collect();
+        if sources != dests {
+            return None;
+        }
+        let swapped = self.pieces.iter().map(|(source, dest)| (*dest, *source));
+        return ShiftMap::normalised(swapped.collect());
+    }
+}
+
+impl<T: PrimInt + Display> Display for ShiftMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_identity() {
+            return write!(f, "identity");
+        }
+        write!(
+            f,
+            "{}",
+            self.pieces
+                .iter()
+                .map(|(source, dest)| {
+                    let shift = if dest.lb() >= source.lb() {
+                        format!("+{}", dest.lb() - source.lb())
+                    } else {
+                        format!("-{}", source.lb() - dest.lb())
+                    };
+                    format!(
+                        "{}..{} -> {}..{} ({})",
+                        source.lb(),
+                        source.ub(),
+                        dest.lb(),
+                        dest.ub(),
+                        shift
+                    )
+                })
+                .join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example almanac's seed-to-soil and soil-to-fertilizer maps
+    fn seed_to_soil() -> ShiftMap<u64> {
+        return ShiftMap::from_pieces([
+            (HalfInterval::new(98, 2), 50),
+            (HalfInterval::new(50, 48), 52),
+        ])
+        .unwrap();
+    }
+    fn soil_to_fertilizer() -> ShiftMap<u64> {
+        return ShiftMap::from_pieces([
+            (HalfInterval::new(15, 37), 0),
+            (HalfInterval::new(52, 2), 37),
+            (HalfInterval::new(0, 15), 39),
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn lookups() {
+        let map = seed_to_soil();
+        assert_eq!(
+            [79, 14, 55, 13, 98, 99, 100].map(|v| map.get(v)),
+            [81, 14, 57, 13, 50, 51, 100]
+        );
+        assert_eq!(
+            map.map_interval(HalfInterval::new(90, 20)),
+            IntervalSet::from_intervals([HalfInterval::new(50, 2), HalfInterval::new(92, 18)])
+        );
+        assert_eq!(
+            map.to_string(),
+            "50..98 -> 52..100 (+2)\n98..100 -> 50..52 (-48)"
+        );
+        assert_eq!(ShiftMap::<u64>::identity().to_string(), "identity");
+
+        // Overlapping sources aren't a function
+        assert_eq!(
+            ShiftMap::from_pieces([(HalfInterval::new(0, 5), 10), (HalfInterval::new(4, 2), 20)]),
+            None
+        );
+        // Pieces which line up are merged, and ones which stay put dropped
+        let merged = ShiftMap::from_pieces([
+            (HalfInterval::new(0, 5), 10),
+            (HalfInterval::new(5, 5), 15),
+            (HalfInterval::new(20, 5), 20),
+        ]);
+        assert_eq!(merged.unwrap().pieces().count(), 1);
+    }
+
+    #[test]
+    fn composition() {
+        let (first, second) = (seed_to_soil(), soil_to_fertilizer());
+        let both = first.then(&second);
+        for v in 0..120 {
+            assert_eq!(both.get(v), second.get(first.get(v)), "at {}", v);
+        }
+        assert_eq!(ShiftMap::identity().then(&first), first);
+        assert_eq!(first.then(&ShiftMap::identity()), first);
+
+        let inverse = first.inverse().unwrap();
+        assert!(first.then(&inverse).is_identity());
+        assert_eq!(inverse.get(50), 98);
+
+        assert!(second.then(&second.inverse().unwrap()).is_identity());
+        // 10..15 would come from both 0..5 and itself
+        let squashed = ShiftMap::from_pieces([(HalfInterval::new(0u64, 5), 10)]).unwrap();
+        assert_eq!(squashed.inverse(), None);
+        assert_eq!(
+            squashed.preimage(&HalfInterval::new(12, 5).into()),
+            IntervalSet::from_intervals([HalfInterval::new(2, 3), HalfInterval::new(12, 5)])
+        );
+
+        let fertilizer = IntervalSet::from(HalfInterval::new(36, 4));
+        assert_eq!(
+            second.preimage(&fertilizer),
+            IntervalSet::from_intervals([HalfInterval::new(0, 1), HalfInterval::new(51, 3)])
+        );
+        assert_eq!(second.map_set(&second.preimage(&fertilizer)), fertilizer);
+    }
+}
@@ -0,0 +1,186 @@
+use std::error;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::common::{input_lines, NotImplementedError};
+use crate::*;
+
+pub type SolverResult = Result<Answer, Box<dyn error::Error>>;
+
+/// The answer to a single part, keeping track of the integer type the part produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    Usize(usize),
+}
+
+impl Answer {
+    pub fn type_name(&self) -> &'static str {
+        return match self {
+            Answer::U32(_) => "u32",
+            Answer::U64(_) => "u64",
+            Answer::I64(_) => "i64",
+            Answer::Usize(_) => "usize",
+        };
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Answer::U32(v) => write!(f, "{}", v),
+            Answer::U64(v) => write!(f, "{}", v),
+            Answer::I64(v) => write!(f, "{}", v),
+            Answer::Usize(v) => write!(f, "{}", v),
+        };
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(v: u32) -> Answer {
+        return Answer::U32(v);
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(v: u64) -> Answer {
+        return Answer::U64(v);
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(v: i64) -> Answer {
+        return Answer::I64(v);
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(v: usize) -> Answer {
+        return Answer::Usize(v);
+    }
+}
+
+/// A single day of the advent calendar, exposing each of its parts through a common interface
+/// so that the CLI (and anything else) can run days generically
+pub trait Solver {
+    /// Day of the advent calendar this solver is for
+    fn day(&self) -> u32;
+
+    /// Title of the puzzle for this day
+    fn name(&self) -> &'static str;
+
+    /// Parts which have been implemented for this day
+    fn parts(&self) -> Vec<u32> {
+        return vec![1, 2];
+    }
+
+    /// Split the raw puzzle input into the pieces `solve_split` reads, borrowed from `input`.
+    ///     These are its lines, unless a day splits its input some other way while solving. The
+    ///     puzzle itself is parsed while solving.
+    fn split<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        return input_lines(input).collect();
+    }
+
+    /// Solve the given part on input from `split`. Parts not listed in `parts` give a
+    ///     `NotImplementedError`
+    fn solve_split(&self, lines: &[&str], part: u32) -> SolverResult;
+
+    /// Split and solve the given part in one go
+    fn solve_part(&self, input: &str, part: u32) -> SolverResult {
+        return self.solve_split(&self.split(input), part);
+    }
+
+    /// Whether `solve_stream` is implemented, for days which never need their whole input at once
+    fn streams(&self) -> bool {
+        return false;
+    }
+
+    /// Solve the given part reading the input as it goes, rather than holding all of it
+    fn solve_stream(&self, _reader: &mut dyn BufRead, _part: u32) -> SolverResult {
+        return Err(NotImplementedError.into());
+    }
+
+    /// `solve_part` for callers still holding the input as owned lines
+    fn solve_lines(&self, lines: &[String], part: u32) -> SolverResult {
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        return self.solve_split(&lines, part);
+    }
+
+    fn has_part(&self, part: u32) -> bool {
+        return self.parts().contains(&part);
+    }
+}
+
+/// Every implemented day, in calendar order
+pub fn registry() -> Vec<Box<dyn Solver>> {
+    return vec![
+        Box::new(day1::Day1),
+        Box::new(day2::Day2),
+        Box::new(day3::Day3),
+        Box::new(day4::Day4),
+        Box::new(day5::Day5),
+        Box::new(day6::Day6),
+        Box::new(day7::Day7),
+        Box::new(day8::Day8),
+        Box::new(day9::Day9),
+        Box::new(day10::Day10),
+        Box::new(day11::Day11),
+        Box::new(day12::Day12),
+        Box::new(day13::Day13),
+        Box::new(day14::Day14),
+        Box::new(day15::Day15),
+        Box::new(day16::Day16),
+        Box::new(day17::Day17),
+        Box::new(day18::Day18),
+    ];
+}
+
+pub fn find_solver(day: u32) -> Option<Box<dyn Solver>> {
+    return registry().into_iter().find(|s| s.day() == day);
+}
+
+/// Run a single part of a single day
+pub fn solve(input: &str, day: u32, part: u32) -> SolverResult {
+    let solver = match find_solver(day) {
+        Some(s) => s,
+        None => return Err(NotImplementedError.into()),
+    };
+    if !solver.has_part(part) {
+        return Err(NotImplementedError.into());
+    }
+
+    return solver.solve_part(input, part);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_in_order() {
+        let days: Vec<u32> = registry().iter().map(|s| s.day()).collect();
+
+        assert_eq!(days, (1..19).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn solve_through_registry() {
+        let input = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
+
+        let result = solve(input, 1, 1).unwrap();
+        assert_eq!(result, Answer::U32(142));
+        assert_eq!(result.to_string(), "142");
+        assert_eq!(result.type_name(), "u32");
+
+        let lines = crate::common::string_to_lines(input);
+        assert_eq!(
+            find_solver(1).unwrap().solve_lines(&lines, 1).unwrap(),
+            result
+        );
+
+        assert!(solve(input, 1, 3).is_err());
+        assert!(solve(input, 25, 1).is_err());
+    }
+}
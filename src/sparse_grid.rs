@@ -0,0 +1,251 @@
+use core::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+use crate::matrix::AoCMatrix;
+
+pub type Coord = (i64, i64);
+
+/// A grid over signed (row, col) coordinates which only stores the cells that have been set,
+///     so it can grow in any direction. Cells are kept sorted by (row, col), which makes
+///     iteration row-major.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SparseGrid<T> {
+    cells: BTreeMap<Coord, T>,
+    /// Inclusive (top left, bottom right) corners of the set cells, None when empty
+    bounds: Option<(Coord, Coord)>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        return SparseGrid {
+            cells: BTreeMap::new(),
+            bounds: None,
+        };
+    }
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+    pub fn contains(&self, pos: Coord) -> bool {
+        self.cells.contains_key(&pos)
+    }
+    pub fn get(&self, pos: Coord) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+    pub fn get_mut(&mut self, pos: Coord) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// Set a cell, growing the bounding box to fit it. Returns the previous value, if any.
+    pub fn insert(&mut self, pos: Coord, value: T) -> Option<T> {
+        self.bounds = match self.bounds {
+            None => Some((pos, pos)),
+            Some((min, max)) => Some((
+                (min.0.min(pos.0), min.1.min(pos.1)),
+                (max.0.max(pos.0), max.1.max(pos.1)),
+            )),
+        };
+        return self.cells.insert(pos, value);
+    }
+
+    /// Clear a cell, shrinking the bounding box if it was on the edge
+    pub fn remove(&mut self, pos: Coord) -> Option<T> {
+        let removed = self.cells.remove(&pos);
+
+        if let (Some(_), Some((min, max))) = (&removed, self.bounds) {
+            let on_edge = pos.0 == min.0 || pos.0 == max.0 || pos.1 == min.1 || pos.1 == max.1;
+            if on_edge {
+                self.bounds = self.compute_bounds();
+            }
+        }
+
+        return removed;
+    }
+
+    fn compute_bounds(&self) -> Option<(Coord, Coord)> {
+        let ((first_row, _), _) = self.cells.first_key_value()?;
+        let ((last_row, _), _) = self.cells.last_key_value()?;
+        let (min_col, max_col) = self.cells.keys().map(|(_, c)| *c).minmax().into_option()?;
+
+        return Some(((*first_row, min_col), (*last_row, max_col)));
+    }
+
+    /// Inclusive (top left, bottom right) corners of the set cells
+    pub fn bounds(&self) -> Option<(Coord, Coord)> {
+        self.bounds
+    }
+    /// Number of rows spanned by the bounding box
+    pub fn n_rows(&self) -> usize {
+        return self
+            .bounds
+            .map_or(0, |(min, max)| (max.0 - min.0 + 1) as usize);
+    }
+    /// Number of columns spanned by the bounding box
+    pub fn n_cols(&self) -> usize {
+        return self
+            .bounds
+            .map_or(0, |(min, max)| (max.1 - min.1 + 1) as usize);
+    }
+
+    /// Set cells in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        return self.cells.iter().map(|(pos, v)| (*pos, v));
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Coord, &mut T)> {
+        return self.cells.iter_mut().map(|(pos, v)| (*pos, v));
+    }
+    pub fn positions(&self) -> impl Iterator<Item = Coord> + '_ {
+        return self.cells.keys().copied();
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Copy a dense matrix, placing its top left corner at `origin`
+    pub fn from_matrix(mat: &AoCMatrix<T>, origin: Coord) -> SparseGrid<T> {
+        let mut grid = SparseGrid::new();
+        for r in 0..mat.n_rows() {
+            for c in 0..mat.n_cols() {
+                let pos = (origin.0 + r as i64, origin.1 + c as i64);
+                grid.insert(pos, mat[(r, c)].clone());
+            }
+        }
+        return grid;
+    }
+
+    /// Densify the bounding box into a matrix, with unset cells as `fill`. The matrix's (0, 0) is
+    ///     the top left corner of `bounds`.
+    pub fn to_matrix(&self, fill: T) -> AoCMatrix<T> {
+        let (min, _) = match self.bounds {
+            Some(b) => b,
+            None => return AoCMatrix::filled_matrix(fill, 0, 0),
+        };
+
+        let mut mat = AoCMatrix::filled_matrix(fill, self.n_rows(), self.n_cols());
+        for (pos, v) in self.iter() {
+            mat[((pos.0 - min.0) as usize, (pos.1 - min.1) as usize)] = v.clone();
+        }
+        return mat;
+    }
+}
+
+impl<T: Clone> From<&AoCMatrix<T>> for SparseGrid<T> {
+    fn from(mat: &AoCMatrix<T>) -> SparseGrid<T> {
+        return SparseGrid::from_matrix(mat, (0, 0));
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> SparseGrid<T> {
+        return SparseGrid::new();
+    }
+}
+
+impl<T> FromIterator<(Coord, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Coord, T)>>(iter: I) -> SparseGrid<T> {
+        let mut grid = SparseGrid::new();
+        grid.extend(iter);
+        return grid;
+    }
+}
+
+impl<T> Extend<(Coord, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Coord, T)>>(&mut self, iter: I) {
+        for (pos, v) in iter {
+            self.insert(pos, v);
+        }
+    }
+}
+
+impl<T> Index<Coord> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Coord) -> &Self::Output {
+        return &self.cells[&pos];
+    }
+}
+
+impl<T> IndexMut<Coord> for SparseGrid<T> {
+    fn index_mut(&mut self, pos: Coord) -> &mut Self::Output {
+        return self.cells.get_mut(&pos).expect("no cell at position");
+    }
+}
+
+impl<T: Display> Display for SparseGrid<T> {
+    /// Renders the bounding box like `AoCMatrix`, with unset cells as `.`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (min, max) = match self.bounds {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        write!(
+            f,
+            "{}",
+            (min.0..=max.0)
+                .map(|r| {
+                    (min.1..=max.1)
+                        .map(|c| match self.get((r, c)) {
+                            Some(v) => v.to_string(),
+                            None => ".".to_string(),
+                        })
+                        .join("")
+                })
+                .join("\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_and_order() {
+        let mut grid: SparseGrid<char> = [((2, -1), 'a'), ((-3, 4), 'b'), ((2, -5), 'c')]
+            .into_iter()
+            .collect();
+
+        assert_eq!(grid.bounds(), Some(((-3, -5), (2, 4))));
+        assert_eq!((grid.n_rows(), grid.n_cols()), (6, 10));
+        assert_eq!(
+            grid.iter().map(|(_, v)| *v).collect::<String>(),
+            "bca".to_string()
+        );
+
+        assert_eq!(grid.remove((-3, 4)), Some('b'));
+        assert_eq!(grid.bounds(), Some(((2, -5), (2, -1))));
+        assert_eq!(grid.to_string(), "c...a");
+
+        grid.remove((2, -5));
+        grid.remove((2, -1));
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid.to_string(), "");
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let mat = AoCMatrix::from_rows(vec![vec!['#', '.'], vec!['.', '#']]);
+        let grid = SparseGrid::from_matrix(&mat, (-1, 3));
+
+        assert_eq!(grid[(-1, 3)], '#');
+        assert_eq!(grid[(0, 4)], '#');
+        assert_eq!(grid.bounds(), Some(((-1, 3), (0, 4))));
+        assert_eq!(grid.to_string(), mat.to_string());
+        assert_eq!(grid.to_matrix(' '), mat);
+
+        let hashes: SparseGrid<char> = grid
+            .iter()
+            .filter(|(_, c)| **c == '#')
+            .map(|(p, c)| (p, *c))
+            .collect();
+        assert_eq!(hashes.to_matrix('.'), mat);
+    }
+}